@@ -1,45 +1,711 @@
-use ark_bls12_381::G1Affine;
-use ark_ec::AffineRepr;
+use std::{marker::PhantomData, slice};
+
+use ark_bls12_381::{
+    g1::{Config, BETA},
+    Fq, Fr, G1Affine, G1Projective,
+};
+use ark_ec::{scalar_mul::glv::GLVConfig, AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use num::{Integer, One, Zero};
+use num_bigint::BigUint;
 use plonky2::{
-    field::extension::Extendable, hash::hash_types::RichField,
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{
+        generator::{GeneratedValues, SimpleGenerator},
+        target::{BoolTarget, Target},
+        witness::{PartitionWitness, Witness, WitnessWrite},
+    },
     plonk::circuit_builder::CircuitBuilder,
+    util::serialization::{Buffer, Read, Write},
 };
+use plonky2_ecdsa::gadgets::biguint::CircuitBuilderBiguint;
 
-use crate::fields::fq_target::FqTarget;
+pub use crate::curves::short_weierstrass::SCALAR_MUL_WINDOW;
+use crate::{
+    curves::{
+        bits_to_biguint,
+        short_weierstrass::{msm, AffineTarget, ProjectiveTarget},
+        split_encoding_flags,
+    },
+    fields::fq_target::FqTarget,
+};
 
-#[derive(Clone, Debug)]
-pub struct G1AffineTarget<F: RichField + Extendable<D>, const D: usize> {
-    pub x: FqTarget<F, D>,
-    pub y: FqTarget<F, D>,
-    pub infinity: bool,
+/// A point of G1 in affine coordinates, see [`AffineTarget`].
+pub type G1AffineTarget<F, const D: usize> = AffineTarget<FqTarget<F, D>>;
+
+/// A point of G1 in projective coordinates, see [`ProjectiveTarget`].
+pub type G1ProjectiveTarget<F, const D: usize> = ProjectiveTarget<FqTarget<F, D>>;
+
+impl<F: RichField + Extendable<D>, const D: usize> G1AffineTarget<F, D> {
+    pub fn constant(builder: &mut CircuitBuilder<F, D>, g1: G1Affine) -> Self {
+        let (x, y) = g1.xy().unwrap_or((Fq::zero(), Fq::zero()));
+        Self {
+            x: FqTarget::constant(builder, x),
+            y: FqTarget::constant(builder, y),
+            infinity: builder.constant_bool(g1.is_zero()),
+        }
+    }
+
+    /// Adds a point whose coordinates and `infinity` flag are set with [`Self::set_witness`].
+    pub fn add_virtual(builder: &mut CircuitBuilder<F, D>) -> Self {
+        let input = builder.add_virtual_targets(25);
+        Self::from_vec(builder, &input)
+    }
+
+    /// Decodes a point from its 48-byte compressed Zcash encoding, as produced by arkworks.
+    /// The compression flag must be set, the point at infinity must be all zero besides its
+    /// flags, `x` must be canonical and on the curve, and `y` is the root that is
+    /// lexicographically largest exactly when the sort flag is set. The point is not checked
+    /// to be in G1.
+    pub fn from_compressed(builder: &mut CircuitBuilder<F, D>, bytes: &[Target]) -> Self {
+        let (flags, x_bits) = split_encoding_flags(builder, bytes);
+        builder.assert_one(flags.is_compressed.target);
+        let x = FqTarget::from_bits(builder, &x_bits);
+        let is_x_zero = x.is_zero(builder);
+        let is_infinity = flags.is_infinity;
+        let is_largest = flags.is_lexicographically_largest;
+        // infinity implies x = 0 and an unset sort flag
+        let x_nonzero_at_infinity =
+            builder.mul_sub(is_infinity.target, is_x_zero.target, is_infinity.target);
+        builder.assert_zero(x_nonzero_at_infinity);
+        let largest_at_infinity = builder.and(is_infinity, is_largest);
+        builder.assert_zero(largest_at_infinity.target);
+
+        // y^2 = x^3 + 4, which is also a square at infinity where x = 0
+        let x_sq = x.mul(builder, &x);
+        let x_cube = x_sq.mul(builder, &x);
+        let b = FqTarget::constant(builder, Fq::from(4));
+        let y_sq = x_cube.add(builder, &b);
+        let false_t = builder._false();
+        let y = y_sq.sqrt_with_sgn(builder, false_t);
+        y.assert_canonical(builder);
+        let y_is_largest = y.is_lexicographically_largest(builder);
+        let neg_y = y.neg(builder);
+        let keep_y = builder.is_equal(y_is_largest.target, is_largest.target);
+        let y = FqTarget::select(builder, &y, &neg_y, &keep_y);
+        let zero = FqTarget::zero(builder);
+
+        Self {
+            x,
+            y: FqTarget::select(builder, &zero, &y, &is_infinity),
+            infinity: is_infinity,
+        }
+    }
+
+    /// Multiplies by a scalar given as little-endian bits, usually the 255 bits of an `Fr`
+    /// element. See [`g1_msm`] for the windowed method.
+    pub fn scalar_mul(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        scalar_bits: &[BoolTarget],
+    ) -> Self {
+        g1_msm(builder, slice::from_ref(self), &[scalar_bits.to_vec()])
+    }
+
+    /// Multiplies by a scalar of at most 255 little-endian bits with the GLV method. A
+    /// generator splits `k` as `k1 + k2·x^2` with halves of [`GLV_HALF_BITS`] bits, which is
+    /// checked in-circuit. As `φ(P) = (βx, y) = -x^2·P` in G1, `k·P = k1·P + k2·(-φ(P))`, and
+    /// the two terms share their doublings in [`g1_msm`]. `self` must be in G1.
+    pub fn glv_scalar_mul(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        scalar_bits: &[BoolTarget],
+    ) -> Self {
+        assert!(scalar_bits.len() <= Fr::MODULUS_BIT_SIZE as usize);
+        let k1_bits = (0..GLV_HALF_BITS)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let k2_bits = (0..GLV_HALF_BITS)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        builder.add_simple_generator(GlvDecompositionGenerator::<F, D> {
+            scalar_bits: scalar_bits.to_vec(),
+            k1_bits: k1_bits.clone(),
+            k2_bits: k2_bits.clone(),
+            _marker: PhantomData,
+        });
+
+        // k = k1 + k2 * x^2 over the integers
+        let k = bits_to_biguint(builder, scalar_bits);
+        let k1 = bits_to_biguint(builder, &k1_bits);
+        let k2 = bits_to_biguint(builder, &k2_bits);
+        let x_sq = builder.constant_biguint(&glv_x_squared());
+        let k2_x_sq = builder.mul_biguint(&k2, &x_sq);
+        let recomposed = builder.add_biguint(&k1, &k2_x_sq);
+        builder.connect_biguint(&k, &recomposed);
+
+        let beta = FqTarget::constant(builder, BETA);
+        let neg_phi = Self {
+            x: self.x.mul(builder, &beta),
+            y: self.y.neg(builder),
+            infinity: self.infinity,
+        };
+        g1_msm(builder, &[self.clone(), neg_phi], &[k1_bits, k2_bits])
+    }
+
+    /// Asserts that a point on the curve is in the prime-order subgroup, using Scott's test
+    /// `σ(P) = -x^2·P` from <https://eprint.iacr.org/2021/1130.pdf>, where `σ(x, y) = (βx, y)`.
+    pub fn assert_in_subgroup(&self, builder: &mut CircuitBuilder<F, D>) {
+        let p = G1ProjectiveTarget::from_affine(builder, self);
+        let neg_x_sq_p = p.mul_by_bls_x(builder).mul_by_bls_x(builder).neg(builder);
+        let sigma_p = G1ProjectiveTarget {
+            x: p.x.mul_const(builder, &BETA),
+            y: p.y,
+            z: p.z,
+        };
+        let is_in_subgroup = sigma_p.is_equal(builder, &neg_x_sq_p);
+        builder.assert_one(is_in_subgroup.target);
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> G1AffineTarget<F, D> {
-    pub fn is_zero(&self) -> bool {
-        self.infinity
+    pub fn to_vec(&self) -> Vec<Target> {
+        self.x
+            .to_vec()
+            .into_iter()
+            .chain(self.y.to_vec())
+            .chain([self.infinity.target])
+            .collect()
     }
 
-    pub fn xy(&self) -> Option<(&self::FqTarget<F, D>, &self::FqTarget<F, D>)> {
-        (!self.infinity).then_some((&self.x, &self.y))
+    pub fn from_vec(builder: &mut CircuitBuilder<F, D>, input: &[Target]) -> Self {
+        let num_limbs = 12;
+        assert_eq!(input.len(), 2 * num_limbs + 1);
+        let infinity = BoolTarget::new_unsafe(input[2 * num_limbs]);
+        builder.assert_bool(infinity);
+        let p = Self {
+            x: FqTarget::from_vec(builder, &input[..num_limbs]),
+            y: FqTarget::from_vec(builder, &input[num_limbs..2 * num_limbs]),
+            infinity,
+        };
+        p.assert_zero_at_infinity(builder);
+        p
     }
 
+    pub fn set_witness<W: WitnessWrite<F>>(&self, pw: &mut W, value: &G1Affine) {
+        let (x, y) = value.xy().unwrap_or((Fq::zero(), Fq::zero()));
+        self.x.set_witness(pw, &x);
+        self.y.set_witness(pw, &y);
+        pw.set_bool_target(self.infinity, value.is_zero());
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> G1ProjectiveTarget<F, D> {
     pub fn constant(builder: &mut CircuitBuilder<F, D>, g1: G1Affine) -> Self {
+        let (x, y, z) = match g1.xy() {
+            Some((x, y)) => (x, y, Fq::one()),
+            None => (Fq::zero(), Fq::one(), Fq::zero()),
+        };
         Self {
-            x: FqTarget::constant(builder, g1.x().unwrap()),
-            y: FqTarget::constant(builder, g1.y().unwrap()),
-            infinity: false,
+            x: FqTarget::constant(builder, x),
+            y: FqTarget::constant(builder, y),
+            z: FqTarget::constant(builder, z),
+        }
+    }
+}
+
+/// Multiplies a base known at circuit-build time by a scalar given as little-endian bits.
+/// The multiples `i * 2^(w * j) * base` of every window `j` are computed natively and embedded
+/// as constants, so the circuit only selects one entry per window and adds them up.
+pub fn fixed_base_mul<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    base: G1Affine,
+    scalar_bits: &[BoolTarget],
+) -> G1AffineTarget<F, D> {
+    let mut window_base = G1Projective::from(base);
+    let mut acc: Option<G1ProjectiveTarget<F, D>> = None;
+    for window in scalar_bits.chunks(SCALAR_MUL_WINDOW) {
+        let multiples = (0..1 << window.len())
+            .scan(G1Projective::zero(), |m, _| {
+                let current = *m;
+                *m += window_base;
+                Some(current)
+            })
+            .collect::<Vec<_>>();
+        let table = G1Projective::normalize_batch(&multiples)
+            .into_iter()
+            .map(|m| G1ProjectiveTarget::constant(builder, m))
+            .collect::<Vec<_>>();
+        let selected = G1ProjectiveTarget::lookup(builder, &table, window);
+        acc = Some(match acc {
+            Some(acc) => acc.add(builder, &selected),
+            None => selected,
+        });
+        window_base *= Fr::from(1u64 << SCALAR_MUL_WINDOW);
+    }
+
+    acc.unwrap_or_else(|| G1ProjectiveTarget::zero(builder))
+        .to_affine(builder)
+}
+
+/// Computes `Σ sᵢ·Pᵢ` over G1, see [`msm`].
+pub fn g1_msm<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    points: &[G1AffineTarget<F, D>],
+    scalars: &[Vec<BoolTarget>],
+) -> G1AffineTarget<F, D> {
+    msm(builder, points, scalars)
+}
+
+/// The bit length of both halves of a GLV decomposition, as `r < 2^128·x^2`.
+pub const GLV_HALF_BITS: usize = 128;
+
+// `x^2`, which is `-λ` for the eigenvalue `λ` of the arkworks GLV endomorphism.
+fn glv_x_squared() -> BigUint {
+    (-<Config as GLVConfig>::LAMBDA).into()
+}
+
+#[derive(Debug)]
+struct GlvDecompositionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    scalar_bits: Vec<BoolTarget>,
+    k1_bits: Vec<BoolTarget>,
+    k2_bits: Vec<BoolTarget>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for GlvDecompositionGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.scalar_bits.iter().map(|b| b.target).collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let k = self
+            .scalar_bits
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |acc, b| {
+                (acc << 1u32) + u32::from(witness.get_bool_target(*b))
+            });
+        let (k2, k1) = k.div_rem(&glv_x_squared());
+
+        for (bits_t, value) in [(&self.k1_bits, k1), (&self.k2_bits, k2)] {
+            for (i, b_t) in bits_t.iter().enumerate() {
+                out_buffer.set_bool_target(*b_t, value.bit(i as u64));
+            }
         }
     }
 
-    pub fn connect(builder: &mut CircuitBuilder<F, D>, lhs: &Self, rhs: &Self) {
-        // The only cases in which two points are equal are
-        // 1. infinity is set on both
-        // 2. infinity is not set on both, and their coordinates are equal
-        assert!((lhs.infinity & rhs.infinity) | (!lhs.infinity) & (!rhs.infinity));
-        FqTarget::connect(builder, &lhs.x, &rhs.x);
-        FqTarget::connect(builder, &lhs.y, &rhs.y);
+    fn id(&self) -> std::string::String {
+        "GlvDecompositionGenerator".to_string()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        dst.write_target_bool_vec(&self.scalar_bits)?;
+        dst.write_target_bool_vec(&self.k1_bits)?;
+        dst.write_target_bool_vec(&self.k2_bits)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            scalar_bits: src.read_target_bool_vec()?,
+            k1_bits: src.read_target_bool_vec()?,
+            k2_bits: src.read_target_bool_vec()?,
+            _marker: PhantomData,
+        })
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct G1PreparedTarget<F: RichField + Extendable<D>, const D: usize>(pub G1AffineTarget<F, D>);
+
+impl<F: RichField + Extendable<D>, const D: usize> G1PreparedTarget<F, D> {
+    /// Prepares a witness-supplied point after asserting that it is on the curve and, when
+    /// `check_subgroup` is set, in the prime-order subgroup.
+    pub fn validated(
+        builder: &mut CircuitBuilder<F, D>,
+        p: G1AffineTarget<F, D>,
+        check_subgroup: bool,
+    ) -> Self {
+        p.assert_on_curve(builder);
+        if check_subgroup {
+            p.assert_in_subgroup(builder);
+        }
+        Self(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use ark_bls12_381::{Fq, Fr, G1Affine, G1Projective};
+    use ark_ec::{CurveGroup, VariableBaseMSM};
+    use ark_ff::{BigInteger, PrimeField, UniformRand};
+    use ark_serialize::CanonicalSerialize;
+    use num::{One, Zero};
+    use num_bigint::BigUint;
+    use plonky2::{
+        field::{goldilocks_field::GoldilocksField, types::Field},
+        iop::{
+            generator::SimpleGenerator,
+            witness::{PartialWitness, WitnessWrite},
+        },
+        plonk::{
+            circuit_builder::CircuitBuilder, circuit_data::CircuitConfig,
+            config::PoseidonGoldilocksConfig,
+        },
+        util::serialization::Buffer,
+    };
+
+    use super::{
+        fixed_base_mul, g1_msm, G1AffineTarget, G1PreparedTarget, GlvDecompositionGenerator,
+        GLV_HALF_BITS,
+    };
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    #[test]
+    fn test_g1_witness() {
+        let rng = &mut rand::thread_rng();
+        let p = G1Affine::rand(rng);
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        let p_expected_t = G1AffineTarget::constant(&mut builder, p);
+
+        let p_vec = p_t.to_vec();
+        let restored_p_t = G1AffineTarget::from_vec(&mut builder, &p_vec);
+        G1AffineTarget::connect(&mut builder, &restored_p_t, &p_expected_t);
+
+        let mut pw = PartialWitness::new();
+        p_t.set_witness(&mut pw, &p);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_g1_add_double() {
+        let rng = &mut rand::thread_rng();
+        let p = G1Affine::rand(rng);
+        let q = G1Affine::rand(rng);
+        let zero = G1Affine::identity();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        let q_t = G1AffineTarget::add_virtual(&mut builder);
+        let zero_t = G1AffineTarget::constant(&mut builder, zero);
+        let neg_p_t = p_t.neg(&mut builder);
+
+        let cases = [
+            (p_t.add(&mut builder, &q_t), (p + q).into_affine()),
+            (p_t.add(&mut builder, &p_t), (p + p).into_affine()),
+            (p_t.double(&mut builder), (p + p).into_affine()),
+            (p_t.add(&mut builder, &neg_p_t), zero),
+            (zero_t.add(&mut builder, &q_t), q),
+            (zero_t.double(&mut builder), zero),
+        ];
+        for (r_t, r) in cases {
+            let r_expected_t = G1AffineTarget::constant(&mut builder, r);
+            G1AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+        }
+
+        let t = builder._true();
+        let f = builder._false();
+        let added_t = p_t.conditional_add(&mut builder, &q_t, &t);
+        let kept_t = p_t.conditional_add(&mut builder, &q_t, &f);
+        let added_expected_t = G1AffineTarget::constant(&mut builder, (p + q).into_affine());
+        let is_added = added_t.is_equal(&mut builder, &added_expected_t);
+        let is_kept = kept_t.is_equal(&mut builder, &p_t);
+        let is_p =
+            G1AffineTarget::select(&mut builder, &p_t, &q_t, &f).is_equal(&mut builder, &p_t);
+        builder.assert_one(is_added.target);
+        builder.assert_one(is_kept.target);
+        builder.assert_zero(is_p.target);
+
+        let mut pw = PartialWitness::new();
+        p_t.set_witness(&mut pw, &p);
+        q_t.set_witness(&mut pw, &q);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_g1_scalar_mul() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        let scalar_t = (0..Fr::MODULUS_BIT_SIZE)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let r_t = p_t.scalar_mul(&mut builder, &scalar_t);
+        let r_expected_t = G1AffineTarget::add_virtual(&mut builder);
+        G1AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+        let data = builder.build::<C>();
+
+        let p = G1Affine::rand(rng);
+        for s in [Fr::zero(), Fr::one(), -Fr::one(), Fr::rand(rng)] {
+            let r_expected = (G1Projective::from(p) * s).into_affine();
+            let mut pw = PartialWitness::new();
+            p_t.set_witness(&mut pw, &p);
+            r_expected_t.set_witness(&mut pw, &r_expected);
+            for (b_t, b) in scalar_t.iter().zip(s.into_bigint().to_bits_le()) {
+                pw.set_bool_target(*b_t, b);
+            }
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_g1_fixed_base_mul() {
+        let rng = &mut rand::thread_rng();
+        let base = G1Affine::rand(rng);
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let scalar_t = (0..Fr::MODULUS_BIT_SIZE)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let r_t = fixed_base_mul(&mut builder, base, &scalar_t);
+        let r_expected_t = G1AffineTarget::add_virtual(&mut builder);
+        G1AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+        let data = builder.build::<C>();
+
+        for s in [Fr::zero(), Fr::one(), -Fr::one(), Fr::rand(rng)] {
+            let r_expected = (base * s).into_affine();
+            let mut pw = PartialWitness::new();
+            r_expected_t.set_witness(&mut pw, &r_expected);
+            for (b_t, b) in scalar_t.iter().zip(s.into_bigint().to_bits_le()) {
+                pw.set_bool_target(*b_t, b);
+            }
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_g1_msm() {
+        let rng = &mut rand::thread_rng();
+        let n = 3;
+        let constant_point = G1Affine::rand(rng);
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut points_t = (0..n)
+            .map(|_| G1AffineTarget::add_virtual(&mut builder))
+            .collect::<Vec<_>>();
+        points_t.push(G1AffineTarget::constant(&mut builder, constant_point));
+        let scalars_t = (0..=n)
+            .map(|_| {
+                (0..Fr::MODULUS_BIT_SIZE)
+                    .map(|_| builder.add_virtual_bool_target_safe())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let r_t = g1_msm(&mut builder, &points_t, &scalars_t);
+        let r_expected_t = G1AffineTarget::add_virtual(&mut builder);
+        G1AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+
+        let mut points = (0..n).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+        points[1] = G1Affine::identity();
+        points.push(constant_point);
+        let mut scalars = (0..=n).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        scalars[2] = Fr::zero();
+        let r_expected = G1Projective::msm(&points, &scalars).unwrap().into_affine();
+
+        let mut pw = PartialWitness::new();
+        for (p_t, p) in points_t.iter().zip(&points).take(n) {
+            p_t.set_witness(&mut pw, p);
+        }
+        for (s_t, s) in scalars_t.iter().zip(&scalars) {
+            for (b_t, b) in s_t.iter().zip(s.into_bigint().to_bits_le()) {
+                pw.set_bool_target(*b_t, b);
+            }
+        }
+        r_expected_t.set_witness(&mut pw, &r_expected);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw).unwrap();
+    }
+
+    #[test]
+    fn test_g1_validated() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        G1PreparedTarget::validated(&mut builder, p_t.clone(), true);
+        let data = builder.build::<C>();
+
+        for p in [G1Affine::rand(rng), G1Affine::identity()] {
+            let mut pw = PartialWitness::new();
+            p_t.set_witness(&mut pw, &p);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g1_add_virtual_nonzero_infinity() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+
+        let mut pw = PartialWitness::new();
+        p_t.x.set_witness(&mut pw, &Fq::rand(rng));
+        p_t.y.set_witness(&mut pw, &Fq::zero());
+        pw.set_bool_target(p_t.infinity, true);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g1_assert_on_curve_fail() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        p_t.assert_on_curve(&mut builder);
+
+        let mut pw = PartialWitness::new();
+        p_t.x.set_witness(&mut pw, &Fq::rand(rng));
+        p_t.y.set_witness(&mut pw, &Fq::rand(rng));
+        pw.set_bool_target(p_t.infinity, false);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g1_assert_in_subgroup_fail() {
+        let rng = &mut rand::thread_rng();
+        // a point on the curve outside G1, as the cofactor is not cleared
+        let p = loop {
+            if let Some(p) = G1Affine::get_point_from_x_unchecked(Fq::rand(rng), false) {
+                break p;
+            }
+        };
+        assert!(!p.is_in_correct_subgroup_assuming_on_curve());
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        G1PreparedTarget::validated(&mut builder, p_t.clone(), true);
+
+        let mut pw = PartialWitness::new();
+        p_t.set_witness(&mut pw, &p);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_g1_glv_scalar_mul() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        let scalar_t = (0..Fr::MODULUS_BIT_SIZE)
+            .map(|_| builder.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let r_t = p_t.glv_scalar_mul(&mut builder, &scalar_t);
+        let r_expected_t = G1AffineTarget::add_virtual(&mut builder);
+        G1AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+        let data = builder.build::<C>();
+
+        let p = G1Affine::rand(rng);
+        for s in [Fr::zero(), Fr::one(), -Fr::one(), Fr::rand(rng)] {
+            let r_expected = (p * s).into_affine();
+            let mut pw = PartialWitness::new();
+            p_t.set_witness(&mut pw, &p);
+            r_expected_t.set_witness(&mut pw, &r_expected);
+            for (b_t, b) in scalar_t.iter().zip(s.into_bigint().to_bits_le()) {
+                pw.set_bool_target(*b_t, b);
+            }
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_glv_decomposition_generator_serialization() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut bits = |n: usize| {
+            (0..n)
+                .map(|_| builder.add_virtual_bool_target_safe())
+                .collect::<Vec<_>>()
+        };
+        let generator = GlvDecompositionGenerator::<F, D> {
+            scalar_bits: bits(Fr::MODULUS_BIT_SIZE as usize),
+            k1_bits: bits(GLV_HALF_BITS),
+            k2_bits: bits(GLV_HALF_BITS),
+            _marker: PhantomData,
+        };
+        let data = builder.build::<C>();
+
+        let mut bytes = vec![];
+        generator.serialize(&mut bytes, &data.common).unwrap();
+        let decoded =
+            GlvDecompositionGenerator::<F, D>::deserialize(&mut Buffer::new(&bytes), &data.common)
+                .unwrap();
+        assert_eq!(decoded.scalar_bits, generator.scalar_bits);
+        assert_eq!(decoded.k1_bits, generator.k1_bits);
+        assert_eq!(decoded.k2_bits, generator.k2_bits);
+    }
+
+    #[test]
+    fn test_g1_from_compressed() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let bytes_t = builder.add_virtual_targets(48);
+        let p_t = G1AffineTarget::from_compressed(&mut builder, &bytes_t);
+        let p_expected_t = G1AffineTarget::add_virtual(&mut builder);
+        G1AffineTarget::connect(&mut builder, &p_t, &p_expected_t);
+        let data = builder.build::<C>();
+
+        let p = G1Affine::rand(rng);
+        for p in [p, -p, G1Affine::identity()] {
+            let mut bytes = vec![];
+            p.serialize_compressed(&mut bytes).unwrap();
+            let mut pw = PartialWitness::new();
+            for (b_t, b) in bytes_t.iter().zip(bytes) {
+                pw.set_target(*b_t, F::from_canonical_u8(b));
+            }
+            p_expected_t.set_witness(&mut pw, &p);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g1_from_compressed_non_canonical() {
+        let rng = &mut rand::thread_rng();
+        // x + p still fits in the 381 bits of the encoding when x is small enough
+        let (p, x_plus_modulus) = loop {
+            let p = G1Affine::rand(rng);
+            let x: BigUint = p.x.into();
+            let x_plus_modulus = x + BigUint::from(Fq::MODULUS);
+            if x_plus_modulus.bits() <= 381 {
+                break (p, x_plus_modulus);
+            }
+        };
+        let mut bytes = vec![];
+        p.serialize_compressed(&mut bytes).unwrap();
+        let flags = bytes[0] & 0b1110_0000;
+        let mut x_bytes = x_plus_modulus.to_bytes_be();
+        x_bytes[0] |= flags;
+
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let bytes_t = builder.add_virtual_targets(48);
+        G1AffineTarget::from_compressed(&mut builder, &bytes_t);
+        let mut pw = PartialWitness::new();
+        for (b_t, b) in bytes_t.iter().zip(x_bytes) {
+            pw.set_target(*b_t, F::from_canonical_u8(b));
+        }
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+}
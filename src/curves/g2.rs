@@ -1,44 +1,223 @@
-use ark_bls12_381::{Fq, Fq2, G2Affine};
+use std::marker::PhantomData;
+
+use ark_bls12_381::{Fq, Fq2, Fr, G2Affine};
 use ark_ec::{short_weierstrass::SWCurveConfig, AffineRepr};
-use ark_ff::BitIteratorBE;
-use num::One;
+use ark_ff::{BitIteratorBE, Field, PrimeField};
+use num::{Integer, One, Zero};
+use num_bigint::BigUint;
 use plonky2::{
-    field::extension::Extendable, hash::hash_types::RichField,
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{
+        generator::{GeneratedValues, SimpleGenerator},
+        target::{BoolTarget, Target},
+        witness::{PartitionWitness, Witness, WitnessWrite},
+    },
     plonk::circuit_builder::CircuitBuilder,
+    util::serialization::{Buffer, Read, Write},
 };
+use plonky2_ecdsa::gadgets::biguint::CircuitBuilderBiguint;
 
 use crate::{
+    curves::{
+        be_bytes_to_le_bits, bits_to_biguint, bool_as_constant,
+        short_weierstrass::{msm, AffineTarget, ProjectiveTarget},
+        split_encoding_flags,
+    },
     fields::{fq2_target::Fq2Target, fq_target::FqTarget},
-    native::miller_loop::G2Projective,
-    utils::constants::BLS_X,
+    final_exponentiation_helpers::frob_coeffs,
+    native::miller_loop::{G2Prepared, G2Projective},
+    utils::constants::{BLS_X, BLS_X_IS_NEGATIVE},
 };
 
-#[derive(Clone, Debug)]
-pub struct G2AffineTarget<F: RichField + Extendable<D>, const D: usize> {
-    x: Fq2Target<F, D>,
-    y: Fq2Target<F, D>,
-    infinity: bool,
-}
+/// A point of the twist carrying G2 in affine coordinates, see [`AffineTarget`].
+pub type G2AffineTarget<F, const D: usize> = AffineTarget<Fq2Target<F, D>>;
+
+/// A point of the twist carrying G2 in projective coordinates, see [`ProjectiveTarget`].
+pub type G2ProjectiveTarget<F, const D: usize> = ProjectiveTarget<Fq2Target<F, D>>;
 
 impl<F: RichField + Extendable<D>, const D: usize> G2AffineTarget<F, D> {
     pub fn constant(builder: &mut CircuitBuilder<F, D>, g2: G2Affine) -> Self {
+        let (x, y) = g2.xy().unwrap_or((Fq2::zero(), Fq2::zero()));
+        Self {
+            x: Fq2Target::constant(builder, x),
+            y: Fq2Target::constant(builder, y),
+            infinity: builder.constant_bool(g2.is_zero()),
+        }
+    }
+
+    /// Adds a point whose coordinates and `infinity` flag are set with [`Self::set_witness`].
+    pub fn add_virtual(builder: &mut CircuitBuilder<F, D>) -> Self {
+        let input = builder.add_virtual_targets(49);
+        Self::from_vec(builder, &input)
+    }
+
+    /// Decodes a point from its 96-byte compressed Zcash encoding, as produced by arkworks:
+    /// `x.c1` and then `x.c0`, with the flags in the top bits of the first byte. The rules are
+    /// those of the G1 encoding, with the sign of `y` given by
+    /// [`Fq2Target::is_lexicographically_largest`]. The point is not checked to be in G2.
+    pub fn from_compressed(builder: &mut CircuitBuilder<F, D>, bytes: &[Target]) -> Self {
+        assert_eq!(bytes.len(), 96);
+        let (flags, c1_bits) = split_encoding_flags(builder, &bytes[..48]);
+        let c0_bits = be_bytes_to_le_bits(builder, &bytes[48..]);
+        builder.assert_one(flags.is_compressed.target);
+        let x = Fq2Target {
+            coeffs: [
+                FqTarget::from_bits(builder, &c0_bits),
+                FqTarget::from_bits(builder, &c1_bits),
+            ],
+        };
+        let is_x_zero = x.is_zero(builder);
+        let is_infinity = flags.is_infinity;
+        let is_largest = flags.is_lexicographically_largest;
+        // infinity implies x = 0 and an unset sort flag
+        let x_nonzero_at_infinity =
+            builder.mul_sub(is_infinity.target, is_x_zero.target, is_infinity.target);
+        builder.assert_zero(x_nonzero_at_infinity);
+        let largest_at_infinity = builder.and(is_infinity, is_largest);
+        builder.assert_zero(largest_at_infinity.target);
+
+        // y^2 = x^3 + 4(u + 1), replaced by a square at infinity as 4(u + 1) is not one
+        let zero = Fq2Target::constant(builder, Fq2::zero());
+        let one = Fq2Target::constant(builder, Fq2::one());
+        let x_sq = x.square(builder);
+        let x_cube = x_sq.mul(builder, &x);
+        let b = Fq2Target::constant(builder, ark_bls12_381::g2::Config::COEFF_B);
+        let y_sq = x_cube.add(builder, &b);
+        let y_sq = Fq2Target::select(builder, &one, &y_sq, &is_infinity);
+        let false_t = builder._false();
+        let y = y_sq.sqrt_with_sgn(builder, false_t);
+        y.assert_canonical(builder);
+        let y_is_largest = y.is_lexicographically_largest(builder);
+        let neg_y = y.neg(builder);
+        let keep_y = builder.is_equal(y_is_largest.target, is_largest.target);
+        let y = Fq2Target::select(builder, &y, &neg_y, &keep_y);
+
+        Self {
+            x,
+            y: Fq2Target::select(builder, &zero, &y, &is_infinity),
+            infinity: is_infinity,
+        }
+    }
+
+    /// The untwist-Frobenius-twist endomorphism `ψ(x, y) = (x̄·c_x, ȳ·c_y)`, which acts as
+    /// multiplication by `x` on G2.
+    pub fn psi(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        let (c_x, c_y) = psi_coeffs();
+        let c_x = Fq2Target::constant(builder, c_x);
+        let c_y = Fq2Target::constant(builder, c_y);
+        let x = self.x.conjugate(builder);
+        let y = self.y.conjugate(builder);
         Self {
-            x: Fq2Target::constant(builder, g2.x().unwrap()),
-            y: Fq2Target::constant(builder, g2.y().unwrap()),
-            infinity: false,
+            x: x.mul(builder, &c_x),
+            y: y.mul(builder, &c_y),
+            infinity: self.infinity,
         }
     }
 
-    fn xy(&self) -> Option<(&self::Fq2Target<F, D>, &self::Fq2Target<F, D>)> {
-        (!self.infinity).then_some((&self.x, &self.y))
+    /// Multiplies by a scalar of at most 255 little-endian bits with a four-dimensional GLS
+    /// decomposition. A generator writes `k` in base `|x|` as `k0 + k1·|x| + k2·|x|^2 +
+    /// k3·|x|^3` with digits of [`GLS_DIGIT_BITS`] bits, which is checked in-circuit. Each
+    /// power of `|x|` is applied to the point with `±ψ`, and the four terms share their
+    /// doublings in [`msm`]. `self` must be in G2.
+    pub fn scalar_mul(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        scalar_bits: &[BoolTarget],
+    ) -> Self {
+        assert!(scalar_bits.len() <= Fr::MODULUS_BIT_SIZE as usize);
+        let digits = (0..4)
+            .map(|_| {
+                (0..GLS_DIGIT_BITS)
+                    .map(|_| builder.add_virtual_bool_target_safe())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        builder.add_simple_generator(GlsDecompositionGenerator::<F, D> {
+            scalar_bits: scalar_bits.to_vec(),
+            digits: digits.clone(),
+            _marker: PhantomData,
+        });
+
+        // k = k0 + |x| * (k1 + |x| * (k2 + |x| * k3)) over the integers
+        let x = builder.constant_biguint(&BigUint::from(BLS_X));
+        let mut recomposed = bits_to_biguint(builder, &digits[3]);
+        for digit in digits[..3].iter().rev() {
+            let digit = bits_to_biguint(builder, digit);
+            let shifted = builder.mul_biguint(&recomposed, &x);
+            recomposed = builder.add_biguint(&digit, &shifted);
+        }
+        let k = bits_to_biguint(builder, scalar_bits);
+        builder.connect_biguint(&k, &recomposed);
+
+        // |x|·P is ψ(P) or -ψ(P) depending on the sign of x
+        let mut points = vec![self.clone()];
+        for i in 1..4 {
+            let next = points[i - 1].psi(builder);
+            points.push(match BLS_X_IS_NEGATIVE {
+                true => next.neg(builder),
+                false => next,
+            });
+        }
+        msm(builder, &points, &digits)
+    }
+
+    /// Asserts that a point on the twist is in the prime-order subgroup, using Scott's test
+    /// `ψ(P) = x·P` from <https://eprint.iacr.org/2021/1130.pdf>.
+    pub fn assert_in_subgroup(&self, builder: &mut CircuitBuilder<F, D>) {
+        let p = G2ProjectiveTarget::from_affine(builder, self);
+        let x_p = p.mul_by_bls_x(builder);
+        let psi_p = self.psi(builder);
+        let psi_p = G2ProjectiveTarget::from_affine(builder, &psi_p);
+        let is_in_subgroup = psi_p.is_equal(builder, &x_p);
+        builder.assert_one(is_in_subgroup.target);
+    }
+
+    // Returns the point, replaced by the generator when it is at infinity so that the line
+    // computations stay well-defined.
+    fn or_generator(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        if bool_as_constant(builder, self.infinity) == Some(false) {
+            return self.clone();
+        }
+        let g = Self::constant(builder, G2Affine::generator());
+        Self {
+            x: Fq2Target::select(builder, &g.x, &self.x, &self.infinity),
+            y: Fq2Target::select(builder, &g.y, &self.y, &self.infinity),
+            infinity: g.infinity,
+        }
     }
 }
 
-#[derive(Clone, Debug)]
-struct G2ProjectiveTarget<F: RichField + Extendable<D>, const D: usize> {
-    x: Fq2Target<F, D>,
-    y: Fq2Target<F, D>,
-    z: Fq2Target<F, D>,
+impl<F: RichField + Extendable<D>, const D: usize> G2AffineTarget<F, D> {
+    pub fn to_vec(&self) -> Vec<Target> {
+        self.x
+            .to_vec()
+            .into_iter()
+            .chain(self.y.to_vec())
+            .chain([self.infinity.target])
+            .collect()
+    }
+
+    pub fn from_vec(builder: &mut CircuitBuilder<F, D>, input: &[Target]) -> Self {
+        let num_limbs = 2 * 12;
+        assert_eq!(input.len(), 2 * num_limbs + 1);
+        let infinity = BoolTarget::new_unsafe(input[2 * num_limbs]);
+        builder.assert_bool(infinity);
+        let q = Self {
+            x: Fq2Target::from_vec(builder, &input[..num_limbs]),
+            y: Fq2Target::from_vec(builder, &input[num_limbs..2 * num_limbs]),
+            infinity,
+        };
+        q.assert_zero_at_infinity(builder);
+        q
+    }
+
+    pub fn set_witness<W: WitnessWrite<F>>(&self, pw: &mut W, value: &G2Affine) {
+        let (x, y) = value.xy().unwrap_or((Fq2::zero(), Fq2::zero()));
+        self.x.set_witness(pw, &x);
+        self.y.set_witness(pw, &y);
+        pw.set_bool_target(self.infinity, value.is_zero());
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> G2ProjectiveTarget<F, D> {
@@ -50,12 +229,6 @@ impl<F: RichField + Extendable<D>, const D: usize> G2ProjectiveTarget<F, D> {
         }
     }
 
-    pub fn connect(builder: &mut CircuitBuilder<F, D>, lhs: &Self, rhs: &Self) {
-        Fq2Target::connect(builder, &lhs.x, &rhs.x);
-        Fq2Target::connect(builder, &lhs.y, &rhs.y);
-        Fq2Target::connect(builder, &lhs.z, &rhs.z);
-    }
-
     fn double_in_place(
         &mut self,
         builder: &mut CircuitBuilder<F, D>,
@@ -63,8 +236,8 @@ impl<F: RichField + Extendable<D>, const D: usize> G2ProjectiveTarget<F, D> {
     ) -> EllCoeffTarget<F, D> {
         let a = self.x.mul(builder, &self.y);
         let a = a.mul_assign_by_fp(builder, two_inv.clone());
-        let b = self.y.simple_square(builder);
-        let c = self.z.simple_square(builder);
+        let b = self.y.square(builder);
+        let c = self.z.square(builder);
         let coeff_b = ark_bls12_381::g2::Config::COEFF_B;
         let coeff_b = Fq2Target::constant(builder, coeff_b);
         let c_double = c.double(builder);
@@ -75,17 +248,17 @@ impl<F: RichField + Extendable<D>, const D: usize> G2ProjectiveTarget<F, D> {
         let g = b.add(builder, &f);
         let g = g.mul_assign_by_fp(builder, two_inv.clone());
         let y_add_z = self.y.add(builder, &self.z);
-        let y_z_squared = y_add_z.simple_square(builder);
+        let y_z_squared = y_add_z.square(builder);
         let b_add_c = b.add(builder, &c);
         let h = y_z_squared.sub(builder, &b_add_c);
         let i = e.sub(builder, &b);
-        let j = self.x.simple_square(builder);
-        let e_square = e.simple_square(builder);
+        let j = self.x.square(builder);
+        let e_square = e.square(builder);
 
         let b_sub_f = b.sub(builder, &f);
 
         self.x = a.mul(builder, &b_sub_f);
-        let g_square = g.simple_square(builder);
+        let g_square = g.square(builder);
         let e_sq_double = e_square.double(builder);
         let e_sq_double = e_sq_double.add(builder, &e_square);
         self.y = g_square.sub(builder, &e_sq_double);
@@ -102,13 +275,13 @@ impl<F: RichField + Extendable<D>, const D: usize> G2ProjectiveTarget<F, D> {
         builder: &mut CircuitBuilder<F, D>,
         q: &G2AffineTarget<F, D>,
     ) -> EllCoeffTarget<F, D> {
-        let (qx, qy) = q.xy().unwrap();
+        let (qx, qy) = (&q.x, &q.y);
         let qy_z = qy.mul(builder, &self.z);
         let theta = self.y.sub(builder, &qy_z);
         let qx_z = qx.mul(builder, &self.z);
         let lambda = self.x.sub(builder, &qx_z);
-        let c = theta.simple_square(builder);
-        let d = lambda.simple_square(builder);
+        let c = theta.square(builder);
+        let d = lambda.square(builder);
         let e = lambda.mul(builder, &d);
         let f = self.z.mul(builder, &c);
         let g = self.x.mul(builder, &d);
@@ -129,78 +302,316 @@ impl<F: RichField + Extendable<D>, const D: usize> G2ProjectiveTarget<F, D> {
     }
 }
 
+/// The bit length of the digits of a GLS decomposition. As `2^64·|x|^3 > 2^255`, every
+/// 255-bit scalar has one.
+pub const GLS_DIGIT_BITS: usize = 64;
+
+// `(c_x, c_y) = ((1 + u)^-((p - 1) / 3), (1 + u)^-((p - 1) / 2))`, the coefficients of ψ.
+fn psi_coeffs() -> (Fq2, Fq2) {
+    let c = frob_coeffs(1);
+    let c_sq = c.square();
+    (c_sq.inverse().unwrap(), (c_sq * c).inverse().unwrap())
+}
+
+#[derive(Debug)]
+struct GlsDecompositionGenerator<F: RichField + Extendable<D>, const D: usize> {
+    scalar_bits: Vec<BoolTarget>,
+    digits: Vec<Vec<BoolTarget>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for GlsDecompositionGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.scalar_bits.iter().map(|b| b.target).collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let mut k = self
+            .scalar_bits
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |acc, b| {
+                (acc << 1u32) + u32::from(witness.get_bool_target(*b))
+            });
+        let x = BigUint::from(BLS_X);
+        for (i, digit_t) in self.digits.iter().enumerate() {
+            // the top digit takes the rest
+            let digit = match i == self.digits.len() - 1 {
+                true => std::mem::take(&mut k),
+                false => {
+                    let (q, r) = k.div_rem(&x);
+                    k = q;
+                    r
+                }
+            };
+            for (j, b_t) in digit_t.iter().enumerate() {
+                out_buffer.set_bool_target(*b_t, digit.bit(j as u64));
+            }
+        }
+    }
+
+    fn id(&self) -> std::string::String {
+        "GlsDecompositionGenerator".to_string()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        dst.write_target_bool_vec(&self.scalar_bits)?;
+        dst.write_usize(self.digits.len())?;
+        for digit in &self.digits {
+            dst.write_target_bool_vec(digit)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let scalar_bits = src.read_target_bool_vec()?;
+        let num_digits = src.read_usize()?;
+        let digits = (0..num_digits)
+            .map(|_| src.read_target_bool_vec())
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            scalar_bits,
+            digits,
+            _marker: PhantomData,
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct G2PreparedTarget<F: RichField + Extendable<D>, const D: usize> {
     /// Stores the coefficients of the line evaluations as calculated in
     /// <https://eprint.iacr.org/2013/722.pdf>
     pub ell_coeffs: Vec<EllCoeffTarget<F, D>>,
-    pub infinity: bool,
+    /// When set, `ell_coeffs` are those of the generator and the pair is selected out of
+    /// the Miller loop.
+    pub infinity: BoolTarget,
 }
 
 pub(crate) type EllCoeffTarget<F, const D: usize> =
     (Fq2Target<F, D>, Fq2Target<F, D>, Fq2Target<F, D>);
 
 impl<F: RichField + Extendable<D>, const D: usize> G2PreparedTarget<F, D> {
-    pub fn is_zero(&self) -> bool {
+    pub fn is_zero(&self) -> BoolTarget {
         self.infinity
     }
 
+    /// Prepares a point known at circuit-build time, so the line coefficients are constants
+    /// and no doubling or addition step is done in-circuit. The Miller loop also multiplies by
+    /// these lines as constants, reducing each coefficient of the product once rather than
+    /// after every product.
+    pub fn from_constant(builder: &mut CircuitBuilder<F, D>, q: G2Affine) -> Self {
+        let prepared = match q.is_zero() {
+            true => G2Prepared::from(G2Affine::generator()),
+            false => G2Prepared::from(q),
+        };
+        let ell_coeffs = prepared
+            .ell_coeffs
+            .into_iter()
+            .map(|(c0, c1, c2)| {
+                (
+                    Fq2Target::constant(builder, c0),
+                    Fq2Target::constant(builder, c1),
+                    Fq2Target::constant(builder, c2),
+                )
+            })
+            .collect();
+
+        Self {
+            ell_coeffs,
+            infinity: builder.constant_bool(q.is_zero()),
+        }
+    }
+
+    /// Prepares a variable point with affine line slopes supplied by generators. Each slope
+    /// is checked with one multiplication and the intermediate points are derived from it,
+    /// which is much cheaper than the projective steps of [`Self::from`]. The lines differ
+    /// from the projective ones by `Fq2` factors, which the final exponentiation removes.
+    ///
+    /// `q` must be in G2, so the slope denominators are non-zero and the slopes unique. Use
+    /// [`Self::validated_line_witness`] to check it in-circuit.
+    pub fn from_line_witness(builder: &mut CircuitBuilder<F, D>, q: G2AffineTarget<F, D>) -> Self {
+        let base = q.or_generator(builder);
+        let (q_x, q_y) = (&base.x, &base.y);
+        let mut ell_coeffs = vec![];
+        let mut r = (q_x.clone(), q_y.clone());
+
+        for i in BitIteratorBE::new([BLS_X]).skip(1) {
+            // tangent slope 3 * x^2 / (2 * y)
+            let x_sq = r.0.square(builder);
+            let num = x_sq.double(builder).add(builder, &x_sq);
+            let den = r.1.double(builder);
+            let slope = num.div_unsafe(builder, &den);
+            let (coeffs, next) = affine_line_step(builder, &slope, &r, &r.0);
+            ell_coeffs.push(coeffs);
+            r = next;
+
+            if i {
+                // chord slope (y - q_y) / (x - q_x)
+                let num = r.1.sub(builder, q_y);
+                let den = r.0.sub(builder, q_x);
+                let slope = num.div_unsafe(builder, &den);
+                let (coeffs, next) = affine_line_step(builder, &slope, &r, q_x);
+                ell_coeffs.push(coeffs);
+                r = next;
+            }
+        }
+
+        Self {
+            ell_coeffs,
+            infinity: q.infinity,
+        }
+    }
+
+    /// Prepares a witness-supplied point after asserting that it is on the twist and, when
+    /// `check_subgroup` is set, in the prime-order subgroup.
+    pub fn validated(
+        builder: &mut CircuitBuilder<F, D>,
+        q: G2AffineTarget<F, D>,
+        check_subgroup: bool,
+    ) -> Self {
+        q.assert_on_curve(builder);
+        if check_subgroup {
+            q.assert_in_subgroup(builder);
+        }
+        Self::from(builder, q)
+    }
+
+    /// [`Self::validated`] with the lines of [`Self::from_line_witness`]. Without
+    /// `check_subgroup`, the caller must still rule out points whose slope denominators
+    /// vanish, such as points of order 2 or 3 on the twist.
+    pub fn validated_line_witness(
+        builder: &mut CircuitBuilder<F, D>,
+        q: G2AffineTarget<F, D>,
+        check_subgroup: bool,
+    ) -> Self {
+        q.assert_on_curve(builder);
+        if check_subgroup {
+            q.assert_in_subgroup(builder);
+        }
+        Self::from_line_witness(builder, q)
+    }
+
     pub fn from(builder: &mut CircuitBuilder<F, D>, q: G2AffineTarget<F, D>) -> Self {
         let one = FqTarget::constant(builder, Fq::one()); // Fq::two
         let two = one.add(builder, &one);
         let two_inv = two.inv(builder);
-        let zero = G2PreparedTarget {
-            ell_coeffs: vec![],
-            infinity: true,
-        };
 
-        q.xy().map_or(zero, |(q_x, q_y)| {
-            let mut ell_coeffs = vec![];
-            let mut r = G2ProjectiveTarget {
-                x: q_x.clone(),
-                y: q_y.clone(),
-                z: Fq2Target::constant(builder, Fq2::one()),
-            };
+        let base = q.or_generator(builder);
+        let mut ell_coeffs = vec![];
+        let mut r = G2ProjectiveTarget {
+            x: base.x.clone(),
+            y: base.y.clone(),
+            z: Fq2Target::constant(builder, Fq2::one()),
+        };
 
-            for i in BitIteratorBE::new([BLS_X]).skip(1) {
-                ell_coeffs.push(r.double_in_place(builder, &two_inv));
+        for i in BitIteratorBE::new([BLS_X]).skip(1) {
+            ell_coeffs.push(r.double_in_place(builder, &two_inv));
 
-                if i {
-                    ell_coeffs.push(r.add_in_place(builder, &q));
-                }
+            if i {
+                ell_coeffs.push(r.add_in_place(builder, &base));
             }
+        }
 
-            Self {
-                ell_coeffs,
-                infinity: false,
-            }
-        })
+        Self {
+            ell_coeffs,
+            infinity: q.infinity,
+        }
     }
 }
 
+// Returns the coefficients `(slope * x - y, -slope, 1)` of the line through `r` with the
+// given slope, and the third point `(slope^2 - x - other_x, slope * (x - x') - y)` on it.
+fn affine_line_step<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    slope: &Fq2Target<F, D>,
+    r: &(Fq2Target<F, D>, Fq2Target<F, D>),
+    other_x: &Fq2Target<F, D>,
+) -> (EllCoeffTarget<F, D>, (Fq2Target<F, D>, Fq2Target<F, D>)) {
+    let (x, y) = r;
+    let slope_x = slope.mul(builder, x);
+    let c0 = slope_x.sub(builder, y);
+    let c1 = slope.neg(builder);
+    let c2 = Fq2Target::constant(builder, Fq2::one());
+
+    let slope_sq = slope.square(builder);
+    let x3 = slope_sq.sub(builder, x).sub(builder, other_x);
+    let x_minus_x3 = x.sub(builder, &x3);
+    let y3 = slope.mul(builder, &x_minus_x3).sub(builder, y);
+
+    ((c0, c1, c2), (x3, y3))
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_bls12_381::{G1Affine, G2Affine};
-    use ark_ec::AffineRepr;
-    use ark_ff::UniformRand;
+    use std::marker::PhantomData;
+
+    use ark_bls12_381::{Fq, Fq2, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{PrimeField, UniformRand};
+    use ark_serialize::CanonicalSerialize;
+    use num::{One, Zero};
+    use num_bigint::BigUint;
     use plonky2::{
-        field::goldilocks_field::GoldilocksField,
-        iop::witness::PartialWitness,
+        field::{goldilocks_field::GoldilocksField, types::Field},
+        iop::{
+            generator::SimpleGenerator,
+            witness::{PartialWitness, WitnessWrite},
+        },
         plonk::{
             circuit_builder::CircuitBuilder, circuit_data::CircuitConfig,
             config::PoseidonGoldilocksConfig,
         },
+        util::serialization::Buffer,
     };
 
-    use super::G2ProjectiveTarget;
+    use super::{G2ProjectiveTarget, GlsDecompositionGenerator, GLS_DIGIT_BITS};
     use crate::{
-        curves::g2::G2AffineTarget, fields::fq_target::FqTarget, native::miller_loop::G2Projective,
+        curves::g2::{G2AffineTarget, G2PreparedTarget},
+        fields::fq_target::FqTarget,
+        native::miller_loop::G2Projective,
     };
 
     type F = GoldilocksField;
     type C = PoseidonGoldilocksConfig;
     const D: usize = 2;
 
+    #[test]
+    fn test_gls_decomposition_generator_serialization() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut bits = |n: usize| {
+            (0..n)
+                .map(|_| builder.add_virtual_bool_target_safe())
+                .collect::<Vec<_>>()
+        };
+        let generator = GlsDecompositionGenerator::<F, D> {
+            scalar_bits: bits(Fr::MODULUS_BIT_SIZE as usize),
+            digits: (0..4).map(|_| bits(GLS_DIGIT_BITS)).collect(),
+            _marker: PhantomData,
+        };
+        let data = builder.build::<C>();
+
+        let mut bytes = vec![];
+        generator.serialize(&mut bytes, &data.common).unwrap();
+        let decoded =
+            GlsDecompositionGenerator::<F, D>::deserialize(&mut Buffer::new(&bytes), &data.common)
+                .unwrap();
+        assert_eq!(decoded.scalar_bits, generator.scalar_bits);
+        assert_eq!(decoded.digits, generator.digits);
+    }
+
     #[test]
     fn test_g2_projective_double_in_place() {
         let config = CircuitConfig::wide_ecc_config();
@@ -246,4 +657,257 @@ mod tests {
         let data = builder.build::<C>();
         let _proof = data.prove(pw);
     }
+
+    #[test]
+    fn test_g2_witness() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        let q_expected_t = G2AffineTarget::add_virtual(&mut builder);
+
+        let q_vec = q_t.to_vec();
+        let restored_q_t = G2AffineTarget::from_vec(&mut builder, &q_vec);
+        G2AffineTarget::connect(&mut builder, &restored_q_t, &q_expected_t);
+        let data = builder.build::<C>();
+
+        for q in [G2Affine::rand(rng), G2Affine::identity()] {
+            let mut pw = PartialWitness::new();
+            q_t.set_witness(&mut pw, &q);
+            q_expected_t.set_witness(&mut pw, &q);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_g2_add_double() {
+        let rng = &mut rand::thread_rng();
+        let p = G2Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let zero = G2Affine::identity();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G2AffineTarget::add_virtual(&mut builder);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        let zero_t = G2AffineTarget::constant(&mut builder, zero);
+        let neg_p_t = p_t.neg(&mut builder);
+
+        let cases = [
+            (p_t.add(&mut builder, &q_t), (p + q).into_affine()),
+            (p_t.add(&mut builder, &p_t), (p + p).into_affine()),
+            (p_t.double(&mut builder), (p + p).into_affine()),
+            (p_t.add(&mut builder, &neg_p_t), zero),
+            (zero_t.add(&mut builder, &q_t), q),
+            (zero_t.double(&mut builder), zero),
+        ];
+        for (r_t, r) in cases {
+            let r_expected_t = G2AffineTarget::constant(&mut builder, r);
+            G2AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+        }
+
+        let f = builder._false();
+        let is_q =
+            G2AffineTarget::select(&mut builder, &p_t, &q_t, &f).is_equal(&mut builder, &q_t);
+        let is_p = p_t.is_equal(&mut builder, &q_t);
+        builder.assert_one(is_q.target);
+        builder.assert_zero(is_p.target);
+
+        let mut pw = PartialWitness::new();
+        p_t.set_witness(&mut pw, &p);
+        q_t.set_witness(&mut pw, &q);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw).unwrap();
+    }
+
+    #[test]
+    fn test_g2_scalar_mul() {
+        let rng = &mut rand::thread_rng();
+        let p = G2Affine::rand(rng);
+        let scalars = [
+            BigUint::zero(),
+            BigUint::one(),
+            BigUint::from(Fr::MODULUS) - BigUint::one(),
+            Fr::rand(rng).into(),
+            (BigUint::one() << 255u32) - BigUint::one(),
+        ];
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let p_t = G2AffineTarget::add_virtual(&mut builder);
+        let mut pw = PartialWitness::new();
+        for k in scalars {
+            let bits = (0..255)
+                .map(|_| builder.add_virtual_bool_target_safe())
+                .collect::<Vec<_>>();
+            for (i, b) in bits.iter().enumerate() {
+                pw.set_bool_target(*b, k.bit(i as u64));
+            }
+            let r_t = p_t.scalar_mul(&mut builder, &bits);
+            let r = (p * Fr::from(k)).into_affine();
+            let r_expected_t = G2AffineTarget::constant(&mut builder, r);
+            G2AffineTarget::connect(&mut builder, &r_t, &r_expected_t);
+        }
+
+        p_t.set_witness(&mut pw, &p);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw).unwrap();
+    }
+
+    #[test]
+    fn test_g2_validated() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        G2PreparedTarget::validated(&mut builder, q_t.clone(), true);
+        let data = builder.build::<C>();
+
+        for q in [G2Affine::rand(rng), G2Affine::identity()] {
+            let mut pw = PartialWitness::new();
+            q_t.set_witness(&mut pw, &q);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_g2_validated_line_witness() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        G2PreparedTarget::validated_line_witness(&mut builder, q_t.clone(), true);
+        let data = builder.build::<C>();
+
+        for q in [G2Affine::rand(rng), G2Affine::identity()] {
+            let mut pw = PartialWitness::new();
+            q_t.set_witness(&mut pw, &q);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g2_validated_line_witness_off_curve() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        G2PreparedTarget::validated_line_witness(&mut builder, q_t.clone(), false);
+
+        let mut pw = PartialWitness::new();
+        q_t.x.set_witness(&mut pw, &Fq2::rand(rng));
+        q_t.y.set_witness(&mut pw, &Fq2::rand(rng));
+        pw.set_bool_target(q_t.infinity, false);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g2_add_virtual_nonzero_infinity() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+
+        let mut pw = PartialWitness::new();
+        q_t.x.set_witness(&mut pw, &Fq2::rand(rng));
+        q_t.y.set_witness(&mut pw, &Fq2::zero());
+        pw.set_bool_target(q_t.infinity, true);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g2_assert_on_curve_fail() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        q_t.assert_on_curve(&mut builder);
+
+        let mut pw = PartialWitness::new();
+        q_t.x.set_witness(&mut pw, &Fq2::rand(rng));
+        q_t.y.set_witness(&mut pw, &Fq2::rand(rng));
+        pw.set_bool_target(q_t.infinity, false);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g2_assert_in_subgroup_fail() {
+        let rng = &mut rand::thread_rng();
+        // a point on the twist outside G2, as the cofactor is not cleared
+        let q = loop {
+            if let Some(q) = G2Affine::get_point_from_x_unchecked(Fq2::rand(rng), false) {
+                break q;
+            }
+        };
+        assert!(!q.is_in_correct_subgroup_assuming_on_curve());
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        G2PreparedTarget::validated(&mut builder, q_t.clone(), true);
+
+        let mut pw = PartialWitness::new();
+        q_t.set_witness(&mut pw, &q);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_g2_from_compressed() {
+        let rng = &mut rand::thread_rng();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let bytes_t = builder.add_virtual_targets(96);
+        let q_t = G2AffineTarget::from_compressed(&mut builder, &bytes_t);
+        let q_expected_t = G2AffineTarget::add_virtual(&mut builder);
+        G2AffineTarget::connect(&mut builder, &q_t, &q_expected_t);
+        let data = builder.build::<C>();
+
+        // a point with x.c1 = 0 has its sign decided by y.c0
+        let q_real_x = loop {
+            let x = Fq2::new(Fq::rand(rng), Fq::zero());
+            if let Some(q) = G2Affine::get_point_from_x_unchecked(x, false) {
+                break q;
+            }
+        };
+        let q = G2Affine::rand(rng);
+        for q in [q, -q, q_real_x, -q_real_x, G2Affine::identity()] {
+            let mut bytes = vec![];
+            q.serialize_compressed(&mut bytes).unwrap();
+            let mut pw = PartialWitness::new();
+            for (b_t, b) in bytes_t.iter().zip(bytes) {
+                pw.set_target(*b_t, F::from_canonical_u8(b));
+            }
+            q_expected_t.set_witness(&mut pw, &q);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_g2_from_compressed_non_canonical() {
+        let rng = &mut rand::thread_rng();
+        // x.c0 + p still fits in the 384 bits of its 48 bytes
+        let q = G2Affine::rand(rng);
+        let mut bytes = vec![];
+        q.serialize_compressed(&mut bytes).unwrap();
+        let c0_plus_modulus = BigUint::from(q.x.c0) + BigUint::from(Fq::MODULUS);
+        let c0_bytes = c0_plus_modulus.to_bytes_be();
+        bytes[96 - c0_bytes.len()..].copy_from_slice(&c0_bytes);
+
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let bytes_t = builder.add_virtual_targets(96);
+        G2AffineTarget::from_compressed(&mut builder, &bytes_t);
+        let mut pw = PartialWitness::new();
+        for (b_t, b) in bytes_t.iter().zip(bytes) {
+            pw.set_target(*b_t, F::from_canonical_u8(b));
+        }
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
 }
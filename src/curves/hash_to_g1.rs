@@ -0,0 +1,268 @@
+use ark_bls12_381::{g1::Config, Fq};
+use ark_ec::{
+    hashing::curve_maps::{swu::SWUConfig, wb::WBConfig},
+    short_weierstrass::SWCurveConfig,
+};
+use num::One;
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField,
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+use crate::{
+    curves::g1::{G1AffineTarget, G1ProjectiveTarget},
+    fields::fq_target::FqTarget,
+};
+
+type IsoConfig = <Config as WBConfig>::IsogenousCurve;
+
+/// Hashes to G1 following `BLS12381G1_XMD:SHA-256_SSWU_RO_` of RFC 9380, starting from the
+/// two field elements `u = hash_to_field(msg, 2)`. The SHA-256 based `expand_message_xmd`
+/// is not part of this gadget. `u` is asserted to be canonical, as its sign is used.
+pub fn hash_to_g1<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    u: &[FqTarget<F, D>; 2],
+) -> G1AffineTarget<F, D> {
+    let q0 = map_to_g1(builder, &u[0]);
+    let q1 = map_to_g1(builder, &u[1]);
+    let q0 = G1ProjectiveTarget::from_affine(builder, &q0);
+    let q1 = G1ProjectiveTarget::from_affine(builder, &q1);
+    let r = q0.add(builder, &q1);
+    clear_cofactor(builder, &r).to_affine(builder)
+}
+
+/// The `map_to_curve` of RFC 9380 for G1: the simplified SWU map to the 11-isogenous curve
+/// `E'`, followed by the isogeny to `E`. The result is not in G1 until its cofactor is
+/// cleared. The exceptional inputs the isogeny sends to infinity make the circuit
+/// unsatisfiable.
+pub fn map_to_g1<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    u: &FqTarget<F, D>,
+) -> G1AffineTarget<F, D> {
+    let (x, y) = sswu(builder, u);
+    iso_map(builder, &x, &y)
+}
+
+/// Multiplies by `h_eff = 1 - x`, which is enough to map `E` onto G1.
+pub fn clear_cofactor<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    p: &G1ProjectiveTarget<F, D>,
+) -> G1ProjectiveTarget<F, D> {
+    let x_p = p.mul_by_bls_x(builder);
+    let neg_x_p = x_p.neg(builder);
+    p.add(builder, &neg_x_p)
+}
+
+// Simplified SWU onto `E'`, with x as a fraction and the square root given by `sqrt_ratio`
+// as in section 6.6.2 of RFC 9380. Returns the affine coordinates on `E'`.
+fn sswu<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    u: &FqTarget<F, D>,
+) -> (FqTarget<F, D>, FqTarget<F, D>) {
+    let a = IsoConfig::COEFF_A;
+    let b = IsoConfig::COEFF_B;
+    let z = IsoConfig::ZETA;
+    // a non-canonical u would flip sgn0(u)
+    u.assert_canonical(builder);
+
+    // x1 = B * (Z^2 u^4 + Z u^2 + 1) / (-A * (Z^2 u^4 + Z u^2)), or B / (Z * A) if that is 0/0
+    let u_sq = u.mul(builder, u);
+    let z_u_sq = u_sq.mul_const(builder, &z);
+    let z_u_sq_sq = z_u_sq.mul(builder, &z_u_sq);
+    let ta = z_u_sq_sq.add(builder, &z_u_sq);
+    let one = FqTarget::constant(builder, Fq::one());
+    let ta_one = ta.add(builder, &one);
+    let num_x1 = ta_one.mul_const(builder, &b);
+    let is_ta_zero = ta.is_zero(builder);
+    let neg_ta = ta.neg(builder);
+    let z_t = FqTarget::constant(builder, z);
+    let div = FqTarget::select(builder, &z_t, &neg_ta, &is_ta_zero);
+    let div = div.mul_const(builder, &a);
+
+    // gx1 = (num_x1^3 + A * num_x1 * div^2 + B * div^3) / div^3
+    let num_x1_sq = num_x1.mul(builder, &num_x1);
+    let div_sq = div.mul(builder, &div);
+    let div_cube = div_sq.mul(builder, &div);
+    let a_div_sq = div_sq.mul_const(builder, &a);
+    let num_gx1 = num_x1_sq.add(builder, &a_div_sq);
+    let num_gx1 = num_gx1.mul(builder, &num_x1);
+    let b_div_cube = div_cube.mul_const(builder, &b);
+    let num_gx1 = num_gx1.add(builder, &b_div_cube);
+
+    // if gx1 is not square, x2 = Z u^2 x1 and gx2 = (Z u^2)^3 gx1, whose root is Z u^3
+    // times the root of Z * gx1 that sqrt_ratio returns
+    let (is_gx1_square, y1) = num_gx1.sqrt_ratio(builder, &div_cube, z);
+    let num_x2 = z_u_sq.mul(builder, &num_x1);
+    let z_u_cube = z_u_sq.mul(builder, u);
+    let y2 = z_u_cube.mul(builder, &y1);
+    let num_x = FqTarget::select(builder, &num_x1, &num_x2, &is_gx1_square);
+    let y = FqTarget::select(builder, &y1, &y2, &is_gx1_square);
+    let x = num_x.div(builder, &div);
+
+    // sgn0(y) = sgn0(u), where y2 comes from an unreduced product and has to be checked
+    y.assert_canonical(builder);
+    let sgn0_u = u.sgn0(builder);
+    let sgn0_y = y.sgn0(builder);
+    let same_sign = builder.is_equal(sgn0_u.target, sgn0_y.target);
+    let neg_y = y.neg(builder);
+    let y = FqTarget::select(builder, &y, &neg_y, &same_sign);
+
+    (x, y)
+}
+
+// The 11-isogeny from `E'` to `E`, with the arkworks coefficients.
+fn iso_map<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: &FqTarget<F, D>,
+    y: &FqTarget<F, D>,
+) -> G1AffineTarget<F, D> {
+    let map = <Config as WBConfig>::ISOGENY_MAP;
+    let x_num = eval_poly(builder, map.x_map_numerator, x);
+    let x_den = eval_poly(builder, map.x_map_denominator, x);
+    let y_num = eval_poly(builder, map.y_map_numerator, x);
+    let y_den = eval_poly(builder, map.y_map_denominator, x);
+
+    let y_num = y_num.mul(builder, y);
+    let infinity = builder._false();
+    G1AffineTarget {
+        x: x_num.div(builder, &x_den),
+        y: y_num.div(builder, &y_den),
+        infinity,
+    }
+}
+
+// Evaluates the polynomial with constant coefficients, lowest degree first, with Horner's rule.
+fn eval_poly<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    coeffs: &[Fq],
+    x: &FqTarget<F, D>,
+) -> FqTarget<F, D> {
+    let (last, rest) = coeffs.split_last().unwrap();
+    let mut acc = FqTarget::constant(builder, *last);
+    for c in rest.iter().rev() {
+        acc = acc.mul(builder, x);
+        let c = FqTarget::constant(builder, *c);
+        acc = acc.add(builder, &c);
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{Fq, G1Affine};
+    use ark_ec::{hashing::curve_maps::swu::SWUConfig, short_weierstrass::SWCurveConfig};
+    use ark_ff::Field;
+    use num::One;
+    use num_bigint::BigUint;
+    use plonky2::{
+        field::goldilocks_field::GoldilocksField,
+        iop::witness::PartialWitness,
+        plonk::{
+            circuit_builder::CircuitBuilder, circuit_data::CircuitConfig,
+            config::PoseidonGoldilocksConfig,
+        },
+    };
+
+    use super::{hash_to_g1, map_to_g1, IsoConfig};
+    use crate::{
+        curves::g1::G1AffineTarget, fields::fq_target::FqTarget, utils::helpers::from_biguint_to_fq,
+    };
+
+    type F = GoldilocksField;
+    type C = PoseidonGoldilocksConfig;
+    const D: usize = 2;
+
+    type Point = (&'static str, &'static str);
+
+    // u, Q0, Q1 and P of the BLS12381G1_XMD:SHA-256_SSWU_RO_ vectors of RFC 9380, appendix J.9.1
+    const VECTORS: [([&str; 2], Point, Point, Point); 5] = [
+        (
+            ["0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941eece7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f", "019b9bd7979f12657976de2884c7cce192b82c177c80e0ec604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9"],
+            ("11a3cce7e1d90975990066b2f2643b9540fa40d6137780df4e753a8054d07580db3b7f1f03396333d4a359d1fe3766fe", "0eeaf6d794e479e270da10fdaf768db4c96b650a74518fc67b04b03927754bac66f3ac720404f339ecdcc028afa091b7"),
+            ("160003aaf1632b13396dbad518effa00fff532f604de1a7fc2082ff4cb0afa2d63b2c32da1bef2bf6c5ca62dc6b72f9c", "0d8bb2d14e20cf9f6036152ed386d79189415b6d015a20133acb4e019139b94e9c146aaad5817f866c95d609a361735e"),
+            ("052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1", "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265"),
+        ),
+        (
+            ["0d921c33f2bad966478a03ca35d05719bdf92d347557ea166e5bba579eea9b83e9afa5c088573c2281410369fbd32951", "003574a00b109ada2f26a37a91f9d1e740dffd8d69ec0c35e1e9f4652c7dba61123e9dd2e76c655d956e2b3462611139"],
+            ("125435adce8e1cbd1c803e7123f45392dc6e326d292499c2c45c5865985fd74fe8f042ecdeeec5ecac80680d04317d80", "0e8828948c989126595ee30e4f7c931cbd6f4570735624fd25aef2fa41d3f79cfb4b4ee7b7e55a8ce013af2a5ba20bf2"),
+            ("11def93719829ecda3b46aa8c31fc3ac9c34b428982b898369608e4f042babee6c77ab9218aad5c87ba785481eff8ae4", "0007c9cef122ccf2efd233d6eb9bfc680aa276652b0661f4f820a653cec1db7ff69899f8e52b8e92b025a12c822a6ce6"),
+            ("03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903", "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d"),
+        ),
+        (
+            ["062d1865eb80ebfa73dcfc45db1ad4266b9f3a93219976a3790ab8d52d3e5f1e62f3b01795e36834b17b70e7b76246d4", "0cdc3e2f271f29c4ff75020857ce6c5d36008c9b48385ea2f2bf6f96f428a3deb798aa033cd482d1cdc8b30178b08e3a"],
+            ("08834484878c217682f6d09a4b51444802fdba3d7f2df9903a0ddadb92130ebbfa807fffa0eabf257d7b48272410afff", "0b318f7ecf77f45a0f038e62d7098221d2dbbca2a394164e2e3fe953dc714ac2cde412d8f2d7f0c03b259e6795a2508e"),
+            ("158418ed6b27e2549f05531a8281b5822b31c3bf3144277fbb977f8d6e2694fedceb7011b3c2b192f23e2a44b2bd106e", "1879074f344471fac5f839e2b4920789643c075792bec5af4282c73f7941cda5aa77b00085eb10e206171b9787c4169f"),
+            ("11e0b079dea29a68f0383ee94fed1b940995272407e3bb916bbf268c263ddd57a6a27200a784cbc248e84f357ce82d98", "03a87ae2caf14e8ee52e51fa2ed8eefe80f02457004ba4d486d6aa1f517c0889501dc7413753f9599b099ebcbbd2d709"),
+        ),
+        (
+            ["010476f6a060453c0b1ad0b628f3e57c23039ee16eea5e71bb87c3b5419b1255dc0e5883322e563b84a29543823c0e86", "0b1a912064fb0554b180e07af7e787f1f883a0470759c03c1b6509eb8ce980d1670305ae7b928226bb58fdc0a419f46e"],
+            ("0cbd7f84ad2c99643fea7a7ac8f52d63d66cefa06d9a56148e58b984b3dd25e1f41ff47154543343949c64f88d48a710", "052c00e4ed52d000d94881a5638ae9274d3efc8bc77bc0e5c650de04a000b2c334a9e80b85282a00f3148dfdface0865"),
+            ("06493fb68f0d513af08be0372f849436a787e7b701ae31cb964d968021d6ba6bd7d26a38aaa5a68e8c21a6b17dc8b579", "02e98f2ccf5802b05ffaac7c20018bc0c0b2fd580216c4aa2275d2909dc0c92d0d0bdc979226adeb57a29933536b6bb4"),
+            ("15f68eaa693b95ccb85215dc65fa81038d69629f70aeee0d0f677cf22285e7bf58d7cb86eefe8f2e9bc3f8cb84fac488", "1807a1d50c29f430b8cafc4f8638dfeeadf51211e1602a5f184443076715f91bb90a48ba1e370edce6ae1062f5e6dd38"),
+        ),
+        (
+            ["0a8ffa7447f6be1c5a2ea4b959c9454b431e29ccc0802bc052413a9c5b4f9aac67a93431bd480d15be1e057c8a08e8c6", "05d487032f602c90fa7625dbafe0f4a49ef4a6b0b33d7bb349ff4cf5410d297fd6241876e3e77b651cfc8191e40a68b7"],
+            ("0cf97e6dbd0947857f3e578231d07b309c622ade08f2c08b32ff372bd90db19467b2563cc997d4407968d4ac80e154f8", "127f0cddf2613058101a5701f4cb9d0861fd6c2a1b8e0afe194fccf586a3201a53874a2761a9ab6d7220c68661a35ab3"),
+            ("092f1acfa62b05f95884c6791fba989bbe58044ee6355d100973bf9553ade52b47929264e6ae770fb264582d8dce512a", "028e6d0169a72cfedb737be45db6c401d3adfb12c58c619c82b93a5dfcccef12290de530b0480575ddc8397cda0bbebf"),
+            ("082aabae8b7dedb0e78aeb619ad3bfd9277a2f77ba7fad20ef6aabdc6c31d19ba5a6d12283553294c1825c4b3ca2dcfe", "05b84ae5a942248eea39e1d91030458c40153f3b654ab7872d779ad1e942856a20c438e8d99bc8abfbf74729ce1f7ac8"),
+        ),
+    ];
+
+    fn fq(hex: &str) -> Fq {
+        from_biguint_to_fq(BigUint::parse_bytes(hex.as_bytes(), 16).unwrap())
+    }
+
+    fn point((x, y): Point) -> G1Affine {
+        G1Affine::new_unchecked(fq(x), fq(y))
+    }
+
+    #[test]
+    fn test_hash_to_g1() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let u_t = [FqTarget::empty(&mut builder), FqTarget::empty(&mut builder)];
+        let q0_t = map_to_g1(&mut builder, &u_t[0]);
+        let q1_t = map_to_g1(&mut builder, &u_t[1]);
+        let p_t = hash_to_g1(&mut builder, &u_t);
+        let expected_t = [(); 3].map(|_| G1AffineTarget::add_virtual(&mut builder));
+        for (r_t, e_t) in [q0_t, q1_t, p_t].iter().zip(&expected_t) {
+            G1AffineTarget::connect(&mut builder, r_t, e_t);
+        }
+        let data = builder.build::<C>();
+
+        for (u, q0, q1, p) in VECTORS {
+            let mut pw = PartialWitness::new();
+            for (u_t, u) in u_t.iter().zip(u) {
+                u_t.set_witness(&mut pw, &fq(u));
+            }
+            for (e_t, e) in expected_t.iter().zip([q0, q1, p]) {
+                e_t.set_witness(&mut pw, &point(e));
+            }
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_map_to_g1_non_square_gx1() {
+        // the first u of the first vector takes the x2 branch of the SWU map
+        let (u, q0, _, _) = VECTORS[0];
+        let u = fq(u[0]);
+        let (a, b, z) = (IsoConfig::COEFF_A, IsoConfig::COEFF_B, IsoConfig::ZETA);
+        let ta = z.square() * u.square().square() + z * u.square();
+        let x1 = -b / a * (Fq::one() + ta.inverse().unwrap());
+        let gx1 = x1 * x1.square() + a * x1 + b;
+        assert!(gx1.legendre().is_qnr());
+
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let u_t = FqTarget::empty(&mut builder);
+        let q0_t = map_to_g1(&mut builder, &u_t);
+        let expected_t = G1AffineTarget::constant(&mut builder, point(q0));
+        G1AffineTarget::connect(&mut builder, &q0_t, &expected_t);
+
+        let mut pw = PartialWitness::new();
+        u_t.set_witness(&mut pw, &u);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw).unwrap();
+    }
+}
@@ -1,2 +1,69 @@
 pub mod g1;
 pub mod g2;
+pub mod hash_to_g1;
+pub mod short_weierstrass;
+
+use plonky2::{
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::target::{BoolTarget, Target},
+    plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_ecdsa::gadgets::biguint::BigUintTarget;
+use plonky2_u32::gadgets::arithmetic_u32::U32Target;
+
+/// Returns the value of `b` if it is known at circuit-build time.
+pub(crate) fn bool_as_constant<F: RichField + Extendable<D>, const D: usize>(
+    builder: &CircuitBuilder<F, D>,
+    b: BoolTarget,
+) -> Option<bool> {
+    builder.target_as_constant(b.target).map(|c| c.is_one())
+}
+
+/// Packs little-endian bits into a [`BigUintTarget`] of 32-bit limbs.
+pub(crate) fn bits_to_biguint<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[BoolTarget],
+) -> BigUintTarget {
+    let limbs = bits
+        .chunks(32)
+        .map(|limb| U32Target(builder.le_sum(limb.iter())))
+        .collect();
+    BigUintTarget { limbs }
+}
+
+/// The flags carried by the top bits of a Zcash point encoding.
+pub(crate) struct EncodingFlagsTarget {
+    pub(crate) is_compressed: BoolTarget,
+    pub(crate) is_infinity: BoolTarget,
+    pub(crate) is_lexicographically_largest: BoolTarget,
+}
+
+/// Splits big-endian bytes into little-endian bits. The bytes are range-checked.
+pub(crate) fn be_bytes_to_le_bits<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bytes: &[Target],
+) -> Vec<BoolTarget> {
+    bytes
+        .iter()
+        .rev()
+        .flat_map(|&b| builder.split_le(b, 8))
+        .collect()
+}
+
+/// Splits 48 big-endian bytes into the flags of the top three bits and the 381 little-endian
+/// bits of the rest. The bytes are range-checked.
+pub(crate) fn split_encoding_flags<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bytes: &[Target],
+) -> (EncodingFlagsTarget, Vec<BoolTarget>) {
+    assert_eq!(bytes.len(), 48);
+    let mut bits = be_bytes_to_le_bits(builder, bytes);
+    let flags = EncodingFlagsTarget {
+        is_lexicographically_largest: bits[381],
+        is_infinity: bits[382],
+        is_compressed: bits[383],
+    };
+    bits.truncate(381);
+    (flags, bits)
+}
@@ -0,0 +1,637 @@
+use std::fmt::Debug;
+
+use ark_bls12_381::{Fq, Fq2};
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ff::{BitIteratorBE, Field};
+use num::{One, Zero};
+use plonky2::{
+    field::extension::Extendable, hash::hash_types::RichField, iop::target::BoolTarget,
+    plonk::circuit_builder::CircuitBuilder,
+};
+
+use crate::{
+    curves::bool_as_constant,
+    fields::{fq2_target::Fq2Target, fq_target::FqTarget},
+    utils::constants::{BLS_X, BLS_X_IS_NEGATIVE},
+};
+
+/// The number of scalar bits handled by each table lookup of [`msm`].
+pub const SCALAR_MUL_WINDOW: usize = 4;
+
+/// The base field of a curve `y^2 = x^3 + b`, which is `Fq` for G1 and `Fq2` for the twist
+/// that carries G2. The point arithmetic below is written once against it.
+pub trait CurveFieldTarget<F: RichField + Extendable<D>, const D: usize>: Clone + Debug {
+    type Native: Field;
+
+    /// The coefficient `b` of the curve over this field.
+    const COEFF_B: Self::Native;
+
+    fn constant(builder: &mut CircuitBuilder<F, D>, c: Self::Native) -> Self;
+    fn connect(builder: &mut CircuitBuilder<F, D>, lhs: &Self, rhs: &Self);
+    fn select(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+        flag: &BoolTarget,
+    ) -> Self;
+    fn is_equal(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> BoolTarget;
+    fn is_zero(&self, builder: &mut CircuitBuilder<F, D>) -> BoolTarget;
+    fn add(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self;
+    fn sub(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self;
+    fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self;
+    fn neg(&self, builder: &mut CircuitBuilder<F, D>) -> Self;
+    fn inv(&self, builder: &mut CircuitBuilder<F, D>) -> Self;
+
+    fn double(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        self.add(builder, self)
+    }
+
+    fn square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        self.mul(builder, self)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CurveFieldTarget<F, D> for FqTarget<F, D> {
+    type Native = Fq;
+
+    const COEFF_B: Fq = ark_bls12_381::g1::Config::COEFF_B;
+
+    fn constant(builder: &mut CircuitBuilder<F, D>, c: Fq) -> Self {
+        FqTarget::constant(builder, c)
+    }
+
+    fn connect(builder: &mut CircuitBuilder<F, D>, lhs: &Self, rhs: &Self) {
+        FqTarget::connect(builder, lhs, rhs)
+    }
+
+    fn select(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+        flag: &BoolTarget,
+    ) -> Self {
+        FqTarget::select(builder, lhs, rhs, flag)
+    }
+
+    fn is_equal(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> BoolTarget {
+        FqTarget::is_equal(self, builder, rhs)
+    }
+
+    fn is_zero(&self, builder: &mut CircuitBuilder<F, D>) -> BoolTarget {
+        FqTarget::is_zero(self, builder)
+    }
+
+    fn add(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        FqTarget::add(self, builder, rhs)
+    }
+
+    fn sub(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        FqTarget::sub(self, builder, rhs)
+    }
+
+    fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        FqTarget::mul(self, builder, rhs)
+    }
+
+    fn neg(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        FqTarget::neg(self, builder)
+    }
+
+    fn inv(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        FqTarget::inv(self, builder)
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CurveFieldTarget<F, D> for Fq2Target<F, D> {
+    type Native = Fq2;
+
+    const COEFF_B: Fq2 = ark_bls12_381::g2::Config::COEFF_B;
+
+    fn constant(builder: &mut CircuitBuilder<F, D>, c: Fq2) -> Self {
+        Fq2Target::constant(builder, c)
+    }
+
+    fn connect(builder: &mut CircuitBuilder<F, D>, lhs: &Self, rhs: &Self) {
+        Fq2Target::connect(builder, lhs, rhs)
+    }
+
+    fn select(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+        flag: &BoolTarget,
+    ) -> Self {
+        Fq2Target::select(builder, lhs, rhs, flag)
+    }
+
+    fn is_equal(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> BoolTarget {
+        Fq2Target::is_equal(self, builder, rhs)
+    }
+
+    fn is_zero(&self, builder: &mut CircuitBuilder<F, D>) -> BoolTarget {
+        Fq2Target::is_zero(self, builder)
+    }
+
+    fn add(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        Fq2Target::add(self, builder, rhs)
+    }
+
+    fn sub(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        Fq2Target::sub(self, builder, rhs)
+    }
+
+    fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        Fq2Target::mul(self, builder, rhs)
+    }
+
+    fn neg(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        Fq2Target::neg(self, builder)
+    }
+
+    fn inv(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        Fq2Target::inv(self, builder)
+    }
+
+    fn double(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        Fq2Target::double(self, builder)
+    }
+
+    fn square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        Fq2Target::square(self, builder)
+    }
+}
+
+/// An affine point whose `infinity` flag is part of the witness. The point at infinity is
+/// represented with coordinates `(0, 0)`, which `from_vec` and `add_virtual` enforce. Points
+/// built from their fields directly must go through [`Self::assert_on_curve`].
+#[derive(Clone, Debug)]
+pub struct AffineTarget<T> {
+    pub x: T,
+    pub y: T,
+    pub infinity: BoolTarget,
+}
+
+impl<T> AffineTarget<T> {
+    pub fn is_zero(&self) -> BoolTarget {
+        self.infinity
+    }
+
+    pub fn xy(&self) -> (&T, &T) {
+        (&self.x, &self.y)
+    }
+
+    pub fn connect<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+    ) where
+        T: CurveFieldTarget<F, D>,
+    {
+        // Both representations are unique, as the point at infinity has coordinates (0, 0)
+        builder.connect(lhs.infinity.target, rhs.infinity.target);
+        T::connect(builder, &lhs.x, &rhs.x);
+        T::connect(builder, &lhs.y, &rhs.y);
+    }
+
+    pub fn neg<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        Self {
+            x: self.x.clone(),
+            y: self.y.neg(builder),
+            infinity: self.infinity,
+        }
+    }
+
+    pub fn add<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        rhs: &Self,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let a = ProjectiveTarget::from_affine(builder, self);
+        let b = ProjectiveTarget::from_affine(builder, rhs);
+        a.add(builder, &b).to_affine(builder)
+    }
+
+    pub fn double<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let a = ProjectiveTarget::from_affine(builder, self);
+        a.double(builder).to_affine(builder)
+    }
+
+    /// Returns `self + rhs` if `flag` is set, and `self` otherwise.
+    pub fn conditional_add<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        rhs: &Self,
+        flag: &BoolTarget,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let sum = self.add(builder, rhs);
+        Self::select(builder, &sum, self, flag)
+    }
+
+    pub fn select<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+        flag: &BoolTarget,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let infinity = builder.select(*flag, lhs.infinity.target, rhs.infinity.target);
+        Self {
+            x: T::select(builder, &lhs.x, &rhs.x, flag),
+            y: T::select(builder, &lhs.y, &rhs.y, flag),
+            infinity: BoolTarget::new_unsafe(infinity),
+        }
+    }
+
+    pub fn is_equal<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        rhs: &Self,
+    ) -> BoolTarget
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let x_eq = self.x.is_equal(builder, &rhs.x);
+        let y_eq = self.y.is_equal(builder, &rhs.y);
+        let infinity_eq = builder.is_equal(self.infinity.target, rhs.infinity.target);
+        let xy_eq = builder.and(x_eq, y_eq);
+        builder.and(xy_eq, infinity_eq)
+    }
+
+    /// Asserts that the point is on `y^2 = x^3 + b`, or is the point at infinity with
+    /// coordinates `(0, 0)`.
+    pub fn assert_on_curve<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) where
+        T: CurveFieldTarget<F, D>,
+    {
+        let zero = T::constant(builder, T::Native::zero());
+        let y_sq = self.y.square(builder);
+        let x_sq = self.x.square(builder);
+        let x_cube = x_sq.mul(builder, &self.x);
+        let b = T::constant(builder, T::COEFF_B);
+        let rhs = x_cube.add(builder, &b);
+        let diff = y_sq.sub(builder, &rhs);
+        let curve_diff = T::select(builder, &zero, &diff, &self.infinity);
+        T::connect(builder, &curve_diff, &zero);
+        self.assert_zero_at_infinity(builder);
+    }
+
+    /// Asserts that the coordinates are `(0, 0)` if the `infinity` flag is set.
+    pub(crate) fn assert_zero_at_infinity<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) where
+        T: CurveFieldTarget<F, D>,
+    {
+        let zero = T::constant(builder, T::Native::zero());
+        for c in [&self.x, &self.y] {
+            let c_at_infinity = T::select(builder, c, &zero, &self.infinity);
+            T::connect(builder, &c_at_infinity, &zero);
+        }
+    }
+}
+
+/// A point in homogeneous projective coordinates `(X : Y : Z)`, with `(0 : 1 : 0)` as the
+/// point at infinity. The formulas are the complete ones of
+/// <https://eprint.iacr.org/2015/1060.pdf> (algorithms 7 and 9), so no case needs a flag.
+#[derive(Clone, Debug)]
+pub struct ProjectiveTarget<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> ProjectiveTarget<T> {
+    pub fn zero<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        Self {
+            x: T::constant(builder, T::Native::zero()),
+            y: T::constant(builder, T::Native::one()),
+            z: T::constant(builder, T::Native::zero()),
+        }
+    }
+
+    pub fn connect<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+    ) where
+        T: CurveFieldTarget<F, D>,
+    {
+        T::connect(builder, &lhs.x, &rhs.x);
+        T::connect(builder, &lhs.y, &rhs.y);
+        T::connect(builder, &lhs.z, &rhs.z);
+    }
+
+    pub fn from_affine<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        p: &AffineTarget<T>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        // keeps constant points constant, so that their multiples are folded
+        match bool_as_constant(builder, p.infinity) {
+            Some(true) => return Self::zero(builder),
+            Some(false) => {
+                return Self {
+                    x: p.x.clone(),
+                    y: p.y.clone(),
+                    z: T::constant(builder, T::Native::one()),
+                }
+            }
+            None => {}
+        }
+        let zero = T::constant(builder, T::Native::zero());
+        let one = T::constant(builder, T::Native::one());
+        Self {
+            x: T::select(builder, &zero, &p.x, &p.infinity),
+            y: T::select(builder, &one, &p.y, &p.infinity),
+            z: T::select(builder, &zero, &one, &p.infinity),
+        }
+    }
+
+    pub fn to_affine<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> AffineTarget<T>
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let zero = T::constant(builder, T::Native::zero());
+        let one = T::constant(builder, T::Native::one());
+        let infinity = self.z.is_zero(builder);
+        // x is zero at infinity, only y has to be cleared
+        let z = T::select(builder, &one, &self.z, &infinity);
+        let z_inv = z.inv(builder);
+        let x = self.x.mul(builder, &z_inv);
+        let y = self.y.mul(builder, &z_inv);
+        AffineTarget {
+            x,
+            y: T::select(builder, &zero, &y, &infinity),
+            infinity,
+        }
+    }
+
+    pub fn select<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        lhs: &Self,
+        rhs: &Self,
+        flag: &BoolTarget,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        Self {
+            x: T::select(builder, &lhs.x, &rhs.x, flag),
+            y: T::select(builder, &lhs.y, &rhs.y, flag),
+            z: T::select(builder, &lhs.z, &rhs.z, flag),
+        }
+    }
+
+    /// Compares two points on the curve with `X1·Z2 = X2·Z1` and `Y1·Z2 = Y2·Z1`.
+    pub fn is_equal<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        rhs: &Self,
+    ) -> BoolTarget
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let x1_z2 = self.x.mul(builder, &rhs.z);
+        let x2_z1 = rhs.x.mul(builder, &self.z);
+        let y1_z2 = self.y.mul(builder, &rhs.z);
+        let y2_z1 = rhs.y.mul(builder, &self.z);
+        let x_eq = x1_z2.is_equal(builder, &x2_z1);
+        let y_eq = y1_z2.is_equal(builder, &y2_z1);
+        builder.and(x_eq, y_eq)
+    }
+
+    pub fn neg<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        Self {
+            x: self.x.clone(),
+            y: self.y.neg(builder),
+            z: self.z.clone(),
+        }
+    }
+
+    // Multiplies by the curve parameter `x` with double-and-add over its constant bits.
+    pub(crate) fn mul_by_bls_x<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let mut acc = self.clone();
+        for bit in BitIteratorBE::without_leading_zeros([BLS_X]).skip(1) {
+            acc = acc.double(builder);
+            if bit {
+                acc = acc.add(builder, self);
+            }
+        }
+        match BLS_X_IS_NEGATIVE {
+            true => acc.neg(builder),
+            false => acc,
+        }
+    }
+
+    // Returns `table[i]`, where `i` has the little-endian bits `index`. A short index, as in
+    // the top window of a scalar, selects among the first entries only.
+    pub(crate) fn lookup<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        table: &[Self],
+        index: &[BoolTarget],
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let mut table = table[..1 << index.len()].to_vec();
+        for bit in index {
+            table = table
+                .chunks(2)
+                .map(|pair| Self::select(builder, &pair[1], &pair[0], bit))
+                .collect();
+        }
+        table.pop().unwrap()
+    }
+
+    pub fn add<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        rhs: &Self,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let b3 = T::constant(builder, T::COEFF_B * T::Native::from(3u64));
+        let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+        let (x2, y2, z2) = (&rhs.x, &rhs.y, &rhs.z);
+
+        let t0 = x1.mul(builder, x2);
+        let t1 = y1.mul(builder, y2);
+        let t2 = z1.mul(builder, z2);
+        let x1_y1 = x1.add(builder, y1);
+        let x2_y2 = x2.add(builder, y2);
+        let t3 = x1_y1.mul(builder, &x2_y2);
+        let t0_t1 = t0.add(builder, &t1);
+        let t3 = t3.sub(builder, &t0_t1);
+        let y1_z1 = y1.add(builder, z1);
+        let y2_z2 = y2.add(builder, z2);
+        let t4 = y1_z1.mul(builder, &y2_z2);
+        let t1_t2 = t1.add(builder, &t2);
+        let t4 = t4.sub(builder, &t1_t2);
+        let x1_z1 = x1.add(builder, z1);
+        let x2_z2 = x2.add(builder, z2);
+        let x3 = x1_z1.mul(builder, &x2_z2);
+        let t0_t2 = t0.add(builder, &t2);
+        let y3 = x3.sub(builder, &t0_t2);
+        let t0_double = t0.double(builder);
+        let t0 = t0_double.add(builder, &t0);
+        let t2 = t2.mul(builder, &b3);
+        let z3 = t1.add(builder, &t2);
+        let t1 = t1.sub(builder, &t2);
+        let y3 = y3.mul(builder, &b3);
+        let t4_y3 = t4.mul(builder, &y3);
+        let t3_t1 = t3.mul(builder, &t1);
+        let x3 = t3_t1.sub(builder, &t4_y3);
+        let y3_t0 = y3.mul(builder, &t0);
+        let t1_z3 = t1.mul(builder, &z3);
+        let y3 = t1_z3.add(builder, &y3_t0);
+        let t0_t3 = t0.mul(builder, &t3);
+        let z3_t4 = z3.mul(builder, &t4);
+        let z3 = z3_t4.add(builder, &t0_t3);
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub fn double<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self
+    where
+        T: CurveFieldTarget<F, D>,
+    {
+        let b3 = T::constant(builder, T::COEFF_B * T::Native::from(3u64));
+        let (x, y, z) = (&self.x, &self.y, &self.z);
+
+        let t0 = y.square(builder);
+        let t0_2 = t0.double(builder);
+        let t0_4 = t0_2.double(builder);
+        let z3 = t0_4.double(builder);
+        let t1 = y.mul(builder, z);
+        let t2 = z.square(builder);
+        let t2 = t2.mul(builder, &b3);
+        let x3 = t2.mul(builder, &z3);
+        let y3 = t0.add(builder, &t2);
+        let z3 = t1.mul(builder, &z3);
+        let t2_double = t2.double(builder);
+        let t2 = t2_double.add(builder, &t2);
+        let t0 = t0.sub(builder, &t2);
+        let y3 = t0.mul(builder, &y3);
+        let y3 = x3.add(builder, &y3);
+        let t1 = x.mul(builder, y);
+        let x3 = t0.mul(builder, &t1);
+        let x3 = x3.double(builder);
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+/// Computes `Σ sᵢ·Pᵢ` with Straus' method, where the scalars are given as little-endian bits
+/// and may differ in length. Every point gets a table of its first `2^w` multiples, and the
+/// windows of [`SCALAR_MUL_WINDOW`] bits are processed from the top with the doublings
+/// shared across all terms. The tables of constant points are folded at build time.
+pub fn msm<F: RichField + Extendable<D>, const D: usize, T: CurveFieldTarget<F, D>>(
+    builder: &mut CircuitBuilder<F, D>,
+    points: &[AffineTarget<T>],
+    scalars: &[Vec<BoolTarget>],
+) -> AffineTarget<T> {
+    assert_eq!(points.len(), scalars.len());
+    let num_bits = scalars.iter().map(Vec::len).max().unwrap_or(0);
+    let zero_bit = builder._false();
+    let scalars = scalars
+        .iter()
+        .map(|s| {
+            let mut s = s.clone();
+            s.resize(num_bits, zero_bit);
+            s
+        })
+        .collect::<Vec<_>>();
+
+    let tables = points
+        .iter()
+        .map(|p| {
+            let p = ProjectiveTarget::from_affine(builder, p);
+            let mut table = vec![ProjectiveTarget::zero(builder), p.clone()];
+            for i in 2..1 << SCALAR_MUL_WINDOW {
+                let next = match i % 2 {
+                    0 => table[i / 2].double(builder),
+                    _ => table[i - 1].add(builder, &p),
+                };
+                table.push(next);
+            }
+            table
+        })
+        .collect::<Vec<_>>();
+
+    let num_windows = num_bits.div_ceil(SCALAR_MUL_WINDOW);
+    let mut acc: Option<ProjectiveTarget<T>> = None;
+    for j in (0..num_windows).rev() {
+        let bits = j * SCALAR_MUL_WINDOW..num_bits.min((j + 1) * SCALAR_MUL_WINDOW);
+        if let Some(a) = acc.as_mut() {
+            for _ in 0..SCALAR_MUL_WINDOW {
+                *a = a.double(builder);
+            }
+        }
+        for (table, scalar) in tables.iter().zip(&scalars) {
+            let selected = ProjectiveTarget::lookup(builder, table, &scalar[bits.clone()]);
+            acc = Some(match acc {
+                Some(acc) => acc.add(builder, &selected),
+                None => selected,
+            });
+        }
+    }
+
+    acc.unwrap_or_else(|| ProjectiveTarget::zero(builder))
+        .to_affine(builder)
+}
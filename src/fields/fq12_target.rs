@@ -1,5 +1,5 @@
-use ark_bls12_381::{Fq, Fq12};
-use ark_ff::Field;
+use ark_bls12_381::{Fq, Fq12, Fq2, Fq6};
+use ark_ff::{AdditiveGroup, Field};
 use itertools::Itertools;
 use num_bigint::BigUint;
 use plonky2::{
@@ -115,77 +115,132 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq12Target<F, D> {
     }
 
     pub fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
-        let a = self;
-        let b = rhs;
-        let mut a0b0_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(11);
-        let mut a0b1_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(11);
-        let mut a1b0_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(11);
-        let mut a1b1_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(11);
-        for i in 0..6 {
-            for j in 0..6 {
-                let coeff00 = a.coeffs[i].mul(builder, &b.coeffs[j]);
-                let coeff01 = a.coeffs[i].mul(builder, &b.coeffs[j + 6]);
-                let coeff10 = a.coeffs[i + 6].mul(builder, &b.coeffs[j]);
-                let coeff11 = a.coeffs[i + 6].mul(builder, &b.coeffs[j + 6]);
-                if i + j < a0b0_coeffs.len() {
-                    a0b0_coeffs[i + j] = a0b0_coeffs[i + j].add(builder, &coeff00);
-                    a0b1_coeffs[i + j] = a0b1_coeffs[i + j].add(builder, &coeff01);
-                    a1b0_coeffs[i + j] = a1b0_coeffs[i + j].add(builder, &coeff10);
-                    a1b1_coeffs[i + j] = a1b1_coeffs[i + j].add(builder, &coeff11);
-                } else {
-                    a0b0_coeffs.push(coeff00);
-                    a0b1_coeffs.push(coeff01);
-                    a1b0_coeffs.push(coeff10);
-                    a1b1_coeffs.push(coeff11);
+        // Karatsuba over Fq6, w^2 = v:
+        // c0 = a0 * b0 + a1 * b1 * v
+        // c1 = (a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1
+        let (a0, a1) = self.convert_to_fq6();
+        let (b0, b1) = rhs.convert_to_fq6();
+
+        let v0 = a0.mul(builder, &b0);
+        let v1 = a1.mul(builder, &b1);
+
+        let v1_nr = v1.mul_by_nonresidue(builder);
+        let c0 = v0.add(builder, &v1_nr);
+
+        let a0_a1 = a0.add(builder, &a1);
+        let b0_b1 = b0.add(builder, &b1);
+        let c1 = a0_a1.mul(builder, &b0_b1);
+        let c1 = c1.sub(builder, &v0);
+        let c1 = c1.sub(builder, &v1);
+
+        Self::from_fq6(c0, c1)
+    }
+
+    /// Returns the products summing to each coefficient of `self * c`, pairing the coefficients
+    /// of `self` with the entries of the constant multiplication matrix. Zero entries are left out.
+    pub(crate) fn mul_const_terms(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        c: &Fq12,
+    ) -> [Vec<(FqTarget<F, D>, FqTarget<F, D>)>; 12] {
+        let mut terms: [Vec<_>; 12] = Default::default();
+        for (i, x) in self.coeffs.iter().enumerate() {
+            let mut e = MyFq12 {
+                coeffs: [Fq::ZERO; 12],
+            };
+            e.coeffs[i] = Fq::ONE;
+            let column: MyFq12 = (Fq12::from(e) * c).into();
+            for (j, k) in column.coeffs.iter().enumerate() {
+                if *k != Fq::ZERO {
+                    let k = FqTarget::constant(builder, *k);
+                    terms[j].push((x.clone(), k));
                 }
             }
         }
+        terms
+    }
 
-        let mut a0b0_minus_a1b1: Vec<FqTarget<F, D>> = Vec::with_capacity(11);
-        let mut a0b1_plus_a1b0: Vec<FqTarget<F, D>> = Vec::with_capacity(11);
-        for i in 0..11 {
-            let a0b0_minus_a1b1_entry = a0b0_coeffs[i].sub(builder, &a1b1_coeffs[i]);
-            let a0b1_plus_a1b0_entry = a0b1_coeffs[i].add(builder, &a1b0_coeffs[i]);
-            a0b0_minus_a1b1.push(a0b0_minus_a1b1_entry);
-            a0b1_plus_a1b0.push(a0b1_plus_a1b0_entry);
-        }
+    /// Multiplies by a constant, reducing each coefficient of the product once.
+    pub fn mul_const(&self, builder: &mut CircuitBuilder<F, D>, c: &Fq12) -> Self {
+        let coeffs = self
+            .mul_const_terms(builder, c)
+            .iter()
+            .map(|terms| FqTarget::sum_of_products(builder, terms))
+            .collect_vec();
+        Self::new(coeffs)
+    }
+
+    pub fn square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        // Complex squaring over Fq6, w^2 = v:
+        // c0 = (a0 - a1) * (a0 - a1 * v) + a0 * a1 + a0 * a1 * v
+        // c1 = 2 * a0 * a1
+        let (a0, a1) = self.convert_to_fq6();
+
+        let v0 = a0.sub(builder, &a1);
+        let a1_nr = a1.mul_by_nonresidue(builder);
+        let v3 = a0.sub(builder, &a1_nr);
+        let v2 = a0.mul(builder, &a1);
+
+        let c0 = v0.mul(builder, &v3);
+        let c0 = c0.add(builder, &v2);
+        let v2_nr = v2.mul_by_nonresidue(builder);
+        let c0 = c0.add(builder, &v2_nr);
+        let c1 = v2.add(builder, &v2);
+
+        Self::from_fq6(c0, c1)
+    }
+
+    /// Granger–Scott squaring, only valid for elements of the cyclotomic subgroup,
+    /// <https://eprint.iacr.org/2009/565.pdf>.
+    pub fn cyclotomic_square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        let fq2 = |i: usize| Fq2Target {
+            coeffs: [self.coeffs[i].clone(), self.coeffs[i + 6].clone()],
+        };
+        let (z0, z1, z2, z3, z4, z5) = (fq2(0), fq2(3), fq2(1), fq2(4), fq2(2), fq2(5));
+
+        let (t0, t1) = fq4_square(builder, &z0, &z1);
+        let (t2, t3) = fq4_square(builder, &z2, &z3);
+        let (t4, t5) = fq4_square(builder, &z4, &z5);
+
+        // z0 = 3 * t0 - 2 * z0
+        let z0 = t0.sub(builder, &z0).double(builder).add(builder, &t0);
+        // z1 = 3 * t1 + 2 * z1
+        let z1 = t1.add(builder, &z1).double(builder).add(builder, &t1);
+        // z2 = 3 * ξ * t5 + 2 * z2
+        let t5 = t5.mul_by_nonresidue(builder);
+        let z2 = t5.add(builder, &z2).double(builder).add(builder, &t5);
+        // z3 = 3 * t4 - 2 * z3
+        let z3 = t4.sub(builder, &z3).double(builder).add(builder, &t4);
+        // z4 = 3 * t2 - 2 * z4
+        let z4 = t2.sub(builder, &z4).double(builder).add(builder, &t2);
+        // z5 = 3 * t3 + 2 * z5
+        let z5 = t3.add(builder, &z5).double(builder).add(builder, &t3);
+
+        let out = [z0, z2, z4, z1, z3, z5];
+        let out_coeffs = out
+            .iter()
+            .map(|x| x.coeffs[0].clone())
+            .chain(out.iter().map(|x| x.coeffs[1].clone()))
+            .collect_vec();
 
-        let const_one = FqTarget::constant(builder, Fq::from(1));
-        let mut out_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(12);
-        for i in 0..6 {
-            if i < 5 {
-                // let coeff: Fq = a0b0_minus_a1b1[i] + Fq::from(1) * a0b0_minus_a1b1[i + 6]
-                //     - a0b1_plus_a1b0[i + 6];
-                let term0 = a0b0_minus_a1b1[i].clone();
-                let term1 = a0b0_minus_a1b1[i + 6].mul(builder, &const_one);
-                let term2 = a0b1_plus_a1b0[i + 6].neg(builder);
-                let term0_plus_term1 = term0.add(builder, &term1);
-                let coeff = term0_plus_term1.add(builder, &term2);
-                out_coeffs.push(coeff);
-            } else {
-                out_coeffs.push(a0b0_minus_a1b1[i].clone());
-            }
-        }
-        for i in 0..6 {
-            if i < 5 {
-                // let coeff: Fq = a0b1_plus_a1b0[i]
-                //     + a0b0_minus_a1b1[i + 6]
-                //     + Fq::from(1) * a0b1_plus_a1b0[i + 6];
-                let term0 = a0b1_plus_a1b0[i].clone();
-                let term1 = a0b0_minus_a1b1[i + 6].clone();
-                let term2 = a0b1_plus_a1b0[i + 6].mul(builder, &const_one);
-                let term0_plus_term1 = term0.add(builder, &term1);
-                let coeff = term0_plus_term1.add(builder, &term2);
-                out_coeffs.push(coeff);
-            } else {
-                out_coeffs.push(a0b1_plus_a1b0[i].clone());
-            }
-        }
         Self {
             coeffs: out_coeffs.try_into().unwrap(),
         }
     }
 
+    /// Karabina compression, only valid for elements of the cyclotomic subgroup.
+    pub fn compress(&self) -> CompressedFq12Target<F, D> {
+        let fq2 = |i: usize| Fq2Target {
+            coeffs: [self.coeffs[i].clone(), self.coeffs[i + 6].clone()],
+        };
+        CompressedFq12Target {
+            g1: fq2(2),
+            g2: fq2(4),
+            g3: fq2(1),
+            g5: fq2(5),
+        }
+    }
+
     pub fn convert_to_fq6(&self) -> (Fq6Target<F, D>, Fq6Target<F, D>) {
         //                   Fq12
         //                  /    \
@@ -261,6 +316,17 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq12Target<F, D> {
         c1: &Fq2Target<F, D>,
         c4: &Fq2Target<F, D>,
     ) -> Self {
+        if let (Some(c0), Some(c1), Some(c4)) = (
+            c0.as_constant(builder),
+            c1.as_constant(builder),
+            c4.as_constant(builder),
+        ) {
+            let c = Fq12::new(
+                Fq6::new(c0, c1, Fq2::ZERO),
+                Fq6::new(Fq2::ZERO, c4, Fq2::ZERO),
+            );
+            return self.mul_const(builder, &c);
+        }
         let (self_c0, self_c1) = self.convert_to_fq6();
 
         let aa = self_c0.clone();
@@ -293,6 +359,87 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq12Target<F, D> {
         ])
     }
 
+    /// Multiplies two sparse elements `c0 + c1 v + c4 v w` with non-zero coefficients at
+    /// positions 0, 1 and 4, returning the coefficients at positions 0, 1, 2, 4 and 5 of the product.
+    pub fn mul_014_by_014(
+        builder: &mut CircuitBuilder<F, D>,
+        a: &[Fq2Target<F, D>; 3],
+        b: &[Fq2Target<F, D>; 3],
+    ) -> [Fq2Target<F, D>; 5] {
+        let [a0, a1, a4] = a;
+        let [b0, b1, b4] = b;
+
+        let a0_b0 = a0.mul(builder, b0);
+        let a1_b1 = a1.mul(builder, b1);
+        let a4_b4 = a4.mul(builder, b4);
+
+        // x0 = a0 * b0 + a4 * b4 * ξ
+        let a4_b4_nr = a4_b4.mul_by_nonresidue(builder);
+        let x0 = a0_b0.add(builder, &a4_b4_nr);
+        // x1 = a0 * b1 + a1 * b0
+        let a0_a1 = a0.add(builder, a1);
+        let b0_b1 = b0.add(builder, b1);
+        let t = a0_a1.mul(builder, &b0_b1);
+        let x1 = t.sub(builder, &a0_b0).sub(builder, &a1_b1);
+        // x2 = a1 * b1
+        let x2 = a1_b1.clone();
+        // x4 = a0 * b4 + a4 * b0
+        let a0_a4 = a0.add(builder, a4);
+        let b0_b4 = b0.add(builder, b4);
+        let t = a0_a4.mul(builder, &b0_b4);
+        let x4 = t.sub(builder, &a0_b0).sub(builder, &a4_b4);
+        // x5 = a1 * b4 + a4 * b1
+        let a1_a4 = a1.add(builder, a4);
+        let b1_b4 = b1.add(builder, b4);
+        let t = a1_a4.mul(builder, &b1_b4);
+        let x5 = t.sub(builder, &a1_b1).sub(builder, &a4_b4);
+
+        [x0, x1, x2, x4, x5]
+    }
+
+    /// Multiplies by a sparse element whose coefficient at position 3 is zero,
+    /// as returned by [`Self::mul_014_by_014`].
+    pub fn mul_by_01245(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        x: &[Fq2Target<F, D>; 5],
+    ) -> Self {
+        let [x0, x1, x2, x4, x5] = x;
+        let (a0, a1) = self.convert_to_fq6();
+        let b0 = Fq6Target::new(vec![
+            x0.coeffs[0].clone(),
+            x1.coeffs[0].clone(),
+            x2.coeffs[0].clone(),
+            x0.coeffs[1].clone(),
+            x1.coeffs[1].clone(),
+            x2.coeffs[1].clone(),
+        ]);
+
+        // Karatsuba over Fq6 with b1 = x4 v + x5 v^2
+        let v0 = a0.mul(builder, &b0);
+        let v1 = a1.clone().mul_by_12(builder, x4, x5);
+
+        let v1_nr = v1.mul_by_nonresidue(builder);
+        let c0 = v0.add(builder, &v1_nr);
+
+        let x1_x4 = x1.add(builder, x4);
+        let x2_x5 = x2.add(builder, x5);
+        let b0_b1 = Fq6Target::new(vec![
+            x0.coeffs[0].clone(),
+            x1_x4.coeffs[0].clone(),
+            x2_x5.coeffs[0].clone(),
+            x0.coeffs[1].clone(),
+            x1_x4.coeffs[1].clone(),
+            x2_x5.coeffs[1].clone(),
+        ]);
+        let a0_a1 = a0.add(builder, &a1);
+        let c1 = a0_a1.mul(builder, &b0_b1);
+        let c1 = c1.sub(builder, &v0);
+        let c1 = c1.sub(builder, &v1);
+
+        Self::from_fq6(c0, c1)
+    }
+
     pub fn div(&self, builder: &mut CircuitBuilder<F, D>, other: &Self) -> Self {
         let inv = other.inv(builder);
         self.mul(builder, &inv)
@@ -353,6 +500,128 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq12Target<F, D> {
     }
 }
 
+/// Karabina's compressed form of a cyclotomic element `(g0 + g1 v + g2 v²) + (g3 + g4 v + g5 v²) w`,
+/// which drops `g0` and `g4`, <https://eprint.iacr.org/2010/542.pdf>.
+#[derive(Clone, Debug)]
+pub struct CompressedFq12Target<F: RichField + Extendable<D>, const D: usize> {
+    pub g1: Fq2Target<F, D>,
+    pub g2: Fq2Target<F, D>,
+    pub g3: Fq2Target<F, D>,
+    pub g5: Fq2Target<F, D>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CompressedFq12Target<F, D> {
+    pub fn square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        let g1_sq = self.g1.square(builder);
+        let g2_sq = self.g2.square(builder);
+        let g3_sq = self.g3.square(builder);
+        let g5_sq = self.g5.square(builder);
+        let g1_g5 = self.g1.mul(builder, &self.g5);
+        let g2_g3 = self.g2.mul(builder, &self.g3);
+
+        // g1' = 3 * (g3^2 + ξ * g2^2) - 2 * g1
+        let t = g2_sq.mul_by_nonresidue(builder).add(builder, &g3_sq);
+        let g1 = t.sub(builder, &self.g1).double(builder).add(builder, &t);
+        // g2' = 3 * (g1^2 + ξ * g5^2) - 2 * g2
+        let t = g5_sq.mul_by_nonresidue(builder).add(builder, &g1_sq);
+        let g2 = t.sub(builder, &self.g2).double(builder).add(builder, &t);
+        // g3' = 6 * ξ * g1 * g5 + 2 * g3
+        let t = g1_g5.double(builder).mul_by_nonresidue(builder);
+        let g3 = t.add(builder, &self.g3).double(builder).add(builder, &t);
+        // g5' = 6 * g2 * g3 + 2 * g5
+        let t = g2_g3.double(builder);
+        let g5 = t.add(builder, &self.g5).double(builder).add(builder, &t);
+
+        Self { g1, g2, g3, g5 }
+    }
+
+    /// Recovers the full elements, with all the divisions sharing one inversion.
+    pub fn decompress_batch(
+        builder: &mut CircuitBuilder<F, D>,
+        elements: &[Self],
+    ) -> Vec<Fq12Target<F, D>> {
+        let zero = Fq2Target::constant(builder, Fq2::ZERO);
+        let one = Fq2Target::constant(builder, Fq2::ONE);
+
+        // g4 = (ξ * g5^2 + 3 * g1^2 - 2 * g2) / (4 * g3) if g3 != 0
+        // g4 = 2 * g1 * g5 / g2 if g3 = 0 and g2 != 0
+        // g4 = 0 if g2 = g3 = 0, in which case the element is one
+        let (nums, dens): (Vec<_>, Vec<_>) = elements
+            .iter()
+            .map(|g| {
+                let g1_sq = g.g1.square(builder);
+                let g5_sq = g.g5.square(builder);
+                let t = g1_sq
+                    .sub(builder, &g.g2)
+                    .double(builder)
+                    .add(builder, &g1_sq);
+                let num = g5_sq.mul_by_nonresidue(builder).add(builder, &t);
+                let den = g.g3.double(builder).double(builder);
+
+                let num_g3_zero = g.g1.mul(builder, &g.g5).double(builder);
+                let g2_is_zero = g.g2.is_zero(builder);
+                let num_g3_zero = Fq2Target::select(builder, &zero, &num_g3_zero, &g2_is_zero);
+                let den_g3_zero = Fq2Target::select(builder, &one, &g.g2, &g2_is_zero);
+
+                let g3_is_zero = g.g3.is_zero(builder);
+                let num = Fq2Target::select(builder, &num_g3_zero, &num, &g3_is_zero);
+                let den = Fq2Target::select(builder, &den_g3_zero, &den, &g3_is_zero);
+                (num, den)
+            })
+            .unzip();
+
+        // the denominators are non-zero, so every g4 is unique
+        let quotients = Fq2Target::div_batch_unsafe(builder, &nums, &dens);
+
+        elements
+            .iter()
+            .zip(quotients)
+            .map(|(g, g4)| {
+                // g0 = ξ * (2 * g4^2 + g3 * g5 - 3 * g1 * g2) + 1
+                let g4_sq = g4.square(builder);
+                let g3_g5 = g.g3.mul(builder, &g.g5);
+                let g1_g2 = g.g1.mul(builder, &g.g2);
+                let t = g4_sq
+                    .sub(builder, &g1_g2)
+                    .double(builder)
+                    .sub(builder, &g1_g2);
+                let t = t.add(builder, &g3_g5).mul_by_nonresidue(builder);
+                let g0 = t.add(builder, &one);
+
+                let out = [
+                    g0,
+                    g.g3.clone(),
+                    g.g1.clone(),
+                    g4,
+                    g.g2.clone(),
+                    g.g5.clone(),
+                ];
+                let out_coeffs = out
+                    .iter()
+                    .map(|x| x.coeffs[0].clone())
+                    .chain(out.iter().map(|x| x.coeffs[1].clone()))
+                    .collect_vec();
+                Fq12Target {
+                    coeffs: out_coeffs.try_into().unwrap(),
+                }
+            })
+            .collect_vec()
+    }
+}
+
+// (a + b * y)^2 = (a^2 + ξ * b^2) + 2 * a * b * y, where Fq4 = Fq2[y] / (y^2 - ξ)
+fn fq4_square<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &Fq2Target<F, D>,
+    b: &Fq2Target<F, D>,
+) -> (Fq2Target<F, D>, Fq2Target<F, D>) {
+    let a_sq = a.square(builder);
+    let b_sq = b.square(builder);
+    let b_sq = b_sq.mul_by_nonresidue(builder);
+    let ab = a.mul(builder, b);
+    (a_sq.add(builder, &b_sq), ab.double(builder))
+}
+
 #[derive(Debug)]
 struct Fq12InverseGenerator<F: RichField + Extendable<D>, const D: usize> {
     x: Fq12Target<F, D>,
@@ -452,7 +721,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq12Target<F, D> {
 #[cfg(test)]
 mod tests {
     use ark_bls12_381::{Fq, Fq12, Fq2};
-    use ark_ff::Field;
+    use ark_ff::{CyclotomicMultSubgroup, Field};
     use ark_std::UniformRand;
     use num_bigint::BigUint;
     use plonky2::{
@@ -464,8 +733,11 @@ mod tests {
         },
     };
 
-    use super::{from_biguint_to_fq, Fq12Target};
-    use crate::fields::{fq2_target::Fq2Target, fq6_target::Fq6Target};
+    use super::{from_biguint_to_fq, CompressedFq12Target, Fq12Target};
+    use crate::{
+        fields::{fq2_target::Fq2Target, fq6_target::Fq6Target},
+        native::final_exponentiation::ark_easy_part,
+    };
 
     type F = GoldilocksField;
     type C = PoseidonGoldilocksConfig;
@@ -519,6 +791,24 @@ mod tests {
         let _proof = data.prove(pw);
     }
 
+    #[test]
+    fn test_fq12_square_circuit() {
+        let rng = &mut rand::thread_rng();
+        let a = Fq12::rand(rng);
+
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let a_t = Fq12Target::constant(&mut builder, a);
+        let a_sq_t = a_t.square(&mut builder);
+        let a_sq_expected_t = Fq12Target::constant(&mut builder, a.square());
+
+        Fq12Target::connect(&mut builder, &a_sq_expected_t, &a_sq_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
     #[test]
     fn test_fq12_inv_circuit() {
         let rng = &mut rand::thread_rng();
@@ -564,6 +854,59 @@ mod tests {
         let _proof = data.prove(pw);
     }
 
+    #[test]
+    fn test_mul_by_constant_014() {
+        let rng = &mut rand::thread_rng();
+        let x: Fq12 = Fq12::rand(rng);
+        let c0: Fq2 = Fq2::rand(rng);
+        let c1: Fq2 = Fq2::rand(rng);
+        let c4: Fq2 = Fq2::rand(rng);
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let c0_t = Fq2Target::constant(&mut builder, c0);
+        let c1_t = Fq2Target::constant(&mut builder, c1);
+        let c4_t = Fq2Target::constant(&mut builder, c4);
+        let x_t = Fq12Target::empty(&mut builder);
+        let mut r_expected = x;
+        r_expected.mul_by_014(&c0, &c1, &c4);
+        let r_t = x_t.mul_by_014(&mut builder, &c0_t, &c1_t, &c4_t);
+
+        let r_expected_t = Fq12Target::constant(&mut builder, r_expected);
+
+        Fq12Target::connect(&mut builder, &r_t, &r_expected_t);
+
+        let mut pw = PartialWitness::new();
+        x_t.set_witness(&mut pw, &x);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_mul_014_by_014() {
+        let rng = &mut rand::thread_rng();
+        let x: Fq12 = Fq12::rand(rng);
+        let a = [Fq2::rand(rng), Fq2::rand(rng), Fq2::rand(rng)];
+        let b = [Fq2::rand(rng), Fq2::rand(rng), Fq2::rand(rng)];
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let a_t = a.map(|c| Fq2Target::constant(&mut builder, c));
+        let b_t = b.map(|c| Fq2Target::constant(&mut builder, c));
+        let r_t = Fq12Target::constant(&mut builder, x);
+        let mut r_expected = x;
+        r_expected.mul_by_014(&a[0], &a[1], &a[2]);
+        r_expected.mul_by_014(&b[0], &b[1], &b[2]);
+        let ab_t = Fq12Target::mul_014_by_014(&mut builder, &a_t, &b_t);
+        let r_t = r_t.mul_by_01245(&mut builder, &ab_t);
+
+        let r_expected_t = Fq12Target::constant(&mut builder, r_expected);
+
+        Fq12Target::connect(&mut builder, &r_t, &r_expected_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
     #[test]
     fn test_from_fq6_to_fq12_casting() {
         let rng = &mut rand::thread_rng();
@@ -648,4 +991,42 @@ mod tests {
         let data = builder.build::<C>();
         let _proof = data.prove(pw);
     }
+
+    #[test]
+    fn test_cyclotomic_square() {
+        let rng = &mut rand::thread_rng();
+        let x = ark_easy_part(Fq12::rand(rng)).unwrap();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x_t = Fq12Target::constant(&mut builder, x);
+
+        let x_sq_t = x_t.cyclotomic_square(&mut builder);
+        let x_sq_expected_t = Fq12Target::constant(&mut builder, x.cyclotomic_square());
+        Fq12Target::connect(&mut builder, &x_sq_t, &x_sq_expected_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_decompress_batch() {
+        let rng = &mut rand::thread_rng();
+        let x = ark_easy_part(Fq12::rand(rng)).unwrap();
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x_t = Fq12Target::constant(&mut builder, x);
+        let one_t = Fq12Target::constant(&mut builder, Fq12::ONE);
+
+        let x_sq_t = x_t.compress().square(&mut builder);
+        let decompressed =
+            CompressedFq12Target::decompress_batch(&mut builder, &[x_sq_t, one_t.compress()]);
+        let x_sq_expected_t = Fq12Target::constant(&mut builder, x.cyclotomic_square());
+        Fq12Target::connect(&mut builder, &decompressed[0], &x_sq_expected_t);
+        Fq12Target::connect(&mut builder, &decompressed[1], &one_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
 }
@@ -1,6 +1,8 @@
+use std::{marker::PhantomData, slice};
+
 use ark_bls12_381::{Fq, Fq2};
 use ark_ec::AdditiveGroup;
-use ark_ff::Field;
+use ark_ff::{batch_inversion, Field};
 use itertools::Itertools;
 use num_bigint::BigUint;
 use num_traits::Zero;
@@ -16,13 +18,16 @@ use plonky2::{
     util::serialization::Buffer,
 };
 use plonky2_ecdsa::gadgets::{
-    biguint::{GeneratedValuesBigUint, WitnessBigUint},
+    biguint::{BigUintTarget, GeneratedValuesBigUint, WitnessBigUint},
     nonnative::CircuitBuilderNonNative,
 };
 
 use crate::{
     fields::fq_target::FqTarget,
-    utils::helpers::{from_biguint_to_fq, sgn0_fq2},
+    utils::{
+        helpers::{from_biguint_to_fq, sgn0_fq2},
+        serialization::{read_biguint_targets, write_biguint_targets},
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -92,6 +97,13 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
         Self { coeffs }
     }
 
+    /// Returns the value of `self` if both its coefficients are circuit constants.
+    pub fn as_constant(&self, builder: &CircuitBuilder<F, D>) -> Option<Fq2> {
+        let c0 = self.coeffs[0].as_constant(builder)?;
+        let c1 = self.coeffs[1].as_constant(builder)?;
+        Some(Fq2::new(c0, c1))
+    }
+
     pub fn add(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
         let coeffs = self
             .coeffs
@@ -144,6 +156,9 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
     }
 
     pub fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        // Karatsuba, u^2 = -1:
+        // c0 = a0 * b0 - a1 * b1
+        // c1 = (a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1
         let a0 = self.coeffs[0].clone();
         let a1 = self.coeffs[1].clone();
 
@@ -155,10 +170,11 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
 
         let c0 = a0_b0.sub(builder, &a1_b1);
 
-        let a0_b1 = a0.mul(builder, &b1);
-        let a1_b0 = a1.mul(builder, &b0);
-
-        let c1 = a0_b1.add(builder, &a1_b0);
+        let a0_a1 = a0.add(builder, &a1);
+        let b0_b1 = b0.add(builder, &b1);
+        let c1 = a0_a1.mul(builder, &b0_b1);
+        let c1 = c1.sub(builder, &a0_b0);
+        let c1 = c1.sub(builder, &a1_b1);
 
         Fq2Target { coeffs: [c0, c1] }
     }
@@ -216,6 +232,42 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
         self.mul(builder, &inv)
     }
 
+    /// Returns `self / rhs`, checked with one multiplication. The output is unconstrained
+    /// when `rhs` is zero, so callers must ensure it is not.
+    pub fn div_unsafe(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        Self::div_batch_unsafe(builder, slice::from_ref(self), slice::from_ref(rhs))
+            .pop()
+            .unwrap()
+    }
+
+    /// Batched [`Self::div_unsafe`], with all the quotients witnessed by one generator
+    /// sharing a single inversion.
+    pub fn div_batch_unsafe(
+        builder: &mut CircuitBuilder<F, D>,
+        nums: &[Self],
+        dens: &[Self],
+    ) -> Vec<Self> {
+        assert_eq!(nums.len(), dens.len());
+        let quotients = (0..nums.len()).map(|_| Self::empty(builder)).collect_vec();
+        // the generator keeps the limbs of the coefficients, two per element
+        let limbs = |xs: &[Self]| {
+            xs.iter()
+                .flat_map(|x| x.coeffs.iter().map(|c| c.target.value.clone()))
+                .collect_vec()
+        };
+        builder.add_simple_generator(Fq2BatchDivGenerator::<F, D> {
+            nums: limbs(nums),
+            dens: limbs(dens),
+            quotients: limbs(&quotients),
+            _marker: PhantomData,
+        });
+        for ((num, den), quotient) in nums.iter().zip(dens).zip(quotients.iter()) {
+            let den_quotient = den.mul(builder, quotient);
+            Self::connect(builder, &den_quotient, num);
+        }
+        quotients
+    }
+
     pub fn simple_square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
         self.mul(builder, self)
     }
@@ -272,6 +324,23 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
         builder.or(sgn_x, is_zero_and_sgn_y)
     }
 
+    /// Returns whether `self > -self` when comparing `c1` first and then `c0`, the sign
+    /// convention of the Zcash G2 encoding. Unlike [`Self::sgn0`], the imaginary part decides
+    /// unless it is zero. `self` must be canonical, see [`Self::assert_canonical`].
+    pub fn is_lexicographically_largest(&self, builder: &mut CircuitBuilder<F, D>) -> BoolTarget {
+        let c0_largest = self.coeffs[0].is_lexicographically_largest(builder);
+        let c1_largest = self.coeffs[1].is_lexicographically_largest(builder);
+        let c1_is_zero = self.coeffs[1].is_zero(builder);
+        let c1_zero_and_c0_largest = builder.and(c1_is_zero, c0_largest);
+        builder.or(c1_largest, c1_zero_and_c0_largest)
+    }
+
+    /// Asserts that both coefficients are canonical.
+    pub fn assert_canonical(&self, builder: &mut CircuitBuilder<F, D>) {
+        self.coeffs[0].assert_canonical(builder);
+        self.coeffs[1].assert_canonical(builder);
+    }
+
     pub fn is_square(&self, builder: &mut CircuitBuilder<F, D>) -> BoolTarget {
         let x = self.coeffs[0].clone();
         let y = self.coeffs[1].clone();
@@ -393,6 +462,76 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     }
 }
 
+#[derive(Debug)]
+struct Fq2BatchDivGenerator<F: RichField + Extendable<D>, const D: usize> {
+    nums: Vec<BigUintTarget>,
+    dens: Vec<BigUintTarget>,
+    quotients: Vec<BigUintTarget>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for Fq2BatchDivGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.nums
+            .iter()
+            .chain(self.dens.iter())
+            .flat_map(|coeff| coeff.limbs.iter().map(|&l| l.0))
+            .collect_vec()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let get_fq2s = |xs: &[BigUintTarget]| {
+            xs.iter()
+                .map(|c| from_biguint_to_fq(witness.get_biguint_target(c.clone())))
+                .tuples()
+                .map(|(c0, c1)| Fq2::new(c0, c1))
+                .collect_vec()
+        };
+        let nums = get_fq2s(&self.nums);
+        let mut dens = get_fq2s(&self.dens);
+        batch_inversion(&mut dens);
+
+        for ((num, den_inv), quotient) in nums.iter().zip(dens).zip(self.quotients.chunks(2)) {
+            let q = *num * den_inv;
+            let q_biguint: Vec<BigUint> = [q.c0, q.c1].iter().map(|&x| x.into()).collect_vec();
+            for (q_t, q) in quotient.iter().zip(q_biguint) {
+                out_buffer.set_biguint_target(q_t, &q);
+            }
+        }
+    }
+
+    fn id(&self) -> std::string::String {
+        "Fq2BatchDivGenerator".to_string()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        write_biguint_targets(dst, &self.nums)?;
+        write_biguint_targets(dst, &self.dens)?;
+        write_biguint_targets(dst, &self.quotients)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            nums: read_biguint_targets(src)?,
+            dens: read_biguint_targets(src)?,
+            quotients: read_biguint_targets(src)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
 #[derive(Debug)]
 struct Fq2SqrtGenerator<F: RichField + Extendable<D>, const D: usize> {
     x: Fq2Target<F, D>,
@@ -470,7 +609,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
     }
 
     pub fn from_vec(builder: &mut CircuitBuilder<F, D>, input: &[Target]) -> Self {
-        let num_limbs = 8;
+        let num_limbs = 12;
         assert_eq!(input.len(), 2 * num_limbs);
         let coeffs = input
             .iter()
@@ -497,21 +636,27 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq2Target<F, D> {
 
 #[cfg(test)]
 mod tests {
+    use std::marker::PhantomData;
+
     use ark_bls12_381::{Fq, Fq2, Fq6Config};
     use ark_ff::{Field, Fp, Fp6Config};
     use ark_std::UniformRand;
     use num_traits::{One, Zero};
     use plonky2::{
         field::{goldilocks_field::GoldilocksField, types::Field as plonky2_field},
-        iop::witness::{PartialWitness, WitnessWrite},
+        iop::{
+            generator::SimpleGenerator,
+            witness::{PartialWitness, WitnessWrite},
+        },
         plonk::{
             circuit_builder::CircuitBuilder, circuit_data::CircuitConfig,
             config::PoseidonGoldilocksConfig,
         },
+        util::serialization::Buffer,
     };
     use rand::Rng;
 
-    use super::Fq2Target;
+    use super::{Fq2BatchDivGenerator, Fq2Target};
     use crate::{fields::fq_target::FqTarget, utils::helpers::sgn0_fq2};
 
     type F = GoldilocksField;
@@ -751,4 +896,32 @@ mod tests {
         let data = builder.build::<C>();
         let _proof = data.prove(pw);
     }
+
+    #[test]
+    fn test_batch_div_generator_serialization() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut limbs = |n: usize| {
+            (0..n)
+                .map(|_| FqTarget::empty(&mut builder).target.value)
+                .collect::<Vec<_>>()
+        };
+        let generator = Fq2BatchDivGenerator::<F, D> {
+            nums: limbs(4),
+            dens: limbs(4),
+            quotients: limbs(4),
+            _marker: PhantomData,
+        };
+        let data = builder.build::<C>();
+
+        let mut bytes = vec![];
+        generator.serialize(&mut bytes, &data.common).unwrap();
+        let decoded =
+            Fq2BatchDivGenerator::<F, D>::deserialize(&mut Buffer::new(&bytes), &data.common)
+                .unwrap();
+        assert_eq!(decoded.dependencies(), generator.dependencies());
+        for (a, b) in decoded.quotients.iter().zip(&generator.quotients) {
+            assert_eq!(a.limbs, b.limbs);
+        }
+    }
 }
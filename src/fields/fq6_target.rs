@@ -128,70 +128,88 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq6Target<F, D> {
     }
 
     pub fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
-        let a = self;
-        let b = rhs;
-        let mut a0b0_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(5);
-        let mut a0b1_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(5);
-        let mut a1b0_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(5);
-        let mut a1b1_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(5);
-        for i in 0..3 {
-            for j in 0..3 {
-                let coeff00 = a.coeffs[i].mul(builder, &b.coeffs[j]);
-                let coeff01 = a.coeffs[i].mul(builder, &b.coeffs[j + 3]);
-                let coeff10 = a.coeffs[i + 3].mul(builder, &b.coeffs[j]);
-                let coeff11 = a.coeffs[i + 3].mul(builder, &b.coeffs[j + 3]);
-                if i + j < a0b0_coeffs.len() {
-                    a0b0_coeffs[i + j] = a0b0_coeffs[i + j].add(builder, &coeff00);
-                    a0b1_coeffs[i + j] = a0b1_coeffs[i + j].add(builder, &coeff01);
-                    a1b0_coeffs[i + j] = a1b0_coeffs[i + j].add(builder, &coeff10);
-                    a1b1_coeffs[i + j] = a1b1_coeffs[i + j].add(builder, &coeff11);
-                } else {
-                    a0b0_coeffs.push(coeff00);
-                    a0b1_coeffs.push(coeff01);
-                    a1b0_coeffs.push(coeff10);
-                    a1b1_coeffs.push(coeff11);
-                }
-            }
-        }
+        // Karatsuba over Fq2, v^3 = ξ:
+        // c0 = ((a1 + a2) * (b1 + b2) - a1 * b1 - a2 * b2) * ξ + a0 * b0
+        // c1 = (a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1 + a2 * b2 * ξ
+        // c2 = (a0 + a2) * (b0 + b2) - a0 * b0 - a2 * b2 + a1 * b1
+        let a0 = Fq2Target::new(vec![self.coeffs[0].clone(), self.coeffs[3].clone()]);
+        let a1 = Fq2Target::new(vec![self.coeffs[1].clone(), self.coeffs[4].clone()]);
+        let a2 = Fq2Target::new(vec![self.coeffs[2].clone(), self.coeffs[5].clone()]);
+
+        let b0 = Fq2Target::new(vec![rhs.coeffs[0].clone(), rhs.coeffs[3].clone()]);
+        let b1 = Fq2Target::new(vec![rhs.coeffs[1].clone(), rhs.coeffs[4].clone()]);
+        let b2 = Fq2Target::new(vec![rhs.coeffs[2].clone(), rhs.coeffs[5].clone()]);
+
+        let v0 = a0.mul(builder, &b0);
+        let v1 = a1.mul(builder, &b1);
+        let v2 = a2.mul(builder, &b2);
+
+        let a1_a2 = a1.add(builder, &a2);
+        let b1_b2 = b1.add(builder, &b2);
+        let t0 = a1_a2.mul(builder, &b1_b2);
+        let t0 = t0.sub(builder, &v1);
+        let t0 = t0.sub(builder, &v2);
+        let t0 = t0.mul_by_nonresidue(builder);
+        let c0 = t0.add(builder, &v0);
+
+        let a0_a1 = a0.add(builder, &a1);
+        let b0_b1 = b0.add(builder, &b1);
+        let t1 = a0_a1.mul(builder, &b0_b1);
+        let t1 = t1.sub(builder, &v0);
+        let t1 = t1.sub(builder, &v1);
+        let v2_nr = v2.mul_by_nonresidue(builder);
+        let c1 = t1.add(builder, &v2_nr);
+
+        let a0_a2 = a0.add(builder, &a2);
+        let b0_b2 = b0.add(builder, &b2);
+        let t2 = a0_a2.mul(builder, &b0_b2);
+        let t2 = t2.sub(builder, &v0);
+        let t2 = t2.sub(builder, &v2);
+        let c2 = t2.add(builder, &v1);
 
-        let mut a0b0_minus_a1b1: Vec<FqTarget<F, D>> = Vec::with_capacity(5);
-        let mut a0b1_plus_a1b0: Vec<FqTarget<F, D>> = Vec::with_capacity(5);
-        for i in 0..5 {
-            let a0b0_minus_a1b1_entry = a0b0_coeffs[i].sub(builder, &a1b1_coeffs[i]);
-            let a0b1_plus_a1b0_entry = a0b1_coeffs[i].add(builder, &a1b0_coeffs[i]);
-            a0b0_minus_a1b1.push(a0b0_minus_a1b1_entry);
-            a0b1_plus_a1b0.push(a0b1_plus_a1b0_entry);
-        }
+        Self::new(vec![
+            c0.coeffs[0].clone(),
+            c1.coeffs[0].clone(),
+            c2.coeffs[0].clone(),
+            c0.coeffs[1].clone(),
+            c1.coeffs[1].clone(),
+            c2.coeffs[1].clone(),
+        ])
+    }
 
-        let const_one = FqTarget::constant(builder, Fq::from(1));
-        let mut out_coeffs: Vec<FqTarget<F, D>> = Vec::with_capacity(6);
-        for i in 0..3 {
-            if i < 2 {
-                let term0 = a0b0_minus_a1b1[i].clone();
-                let term1 = a0b0_minus_a1b1[i + 3].mul(builder, &const_one);
-                let term2 = a0b1_plus_a1b0[i + 3].neg(builder);
-                let term0_plus_term1 = term0.add(builder, &term1);
-                let coeff = term0_plus_term1.add(builder, &term2);
-                out_coeffs.push(coeff);
-            } else {
-                out_coeffs.push(a0b0_minus_a1b1[i].clone());
-            }
-        }
-        for i in 0..3 {
-            if i < 2 {
-                let term0 = a0b1_plus_a1b0[i].clone();
-                let term1 = a0b0_minus_a1b1[i + 3].clone();
-                let term2 = a0b1_plus_a1b0[i + 3].mul(builder, &const_one);
-                let term0_plus_term1 = term0.add(builder, &term1);
-                let coeff = term0_plus_term1.add(builder, &term2);
-                out_coeffs.push(coeff);
-            } else {
-                out_coeffs.push(a0b1_plus_a1b0[i].clone());
-            }
-        }
-        Self {
-            coeffs: out_coeffs.try_into().unwrap(),
-        }
+    pub fn square(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        // Chung–Hasan SQR2, v^3 = ξ:
+        // c0 = a0^2 + 2 * a1 * a2 * ξ
+        // c1 = 2 * a0 * a1 + a2^2 * ξ
+        // c2 = 2 * a0 * a1 + (a0 - a1 + a2)^2 + 2 * a1 * a2 - a0^2 - a2^2
+        let a0 = Fq2Target::new(vec![self.coeffs[0].clone(), self.coeffs[3].clone()]);
+        let a1 = Fq2Target::new(vec![self.coeffs[1].clone(), self.coeffs[4].clone()]);
+        let a2 = Fq2Target::new(vec![self.coeffs[2].clone(), self.coeffs[5].clone()]);
+
+        let s0 = a0.square(builder);
+        let s1 = a0.mul(builder, &a1).double(builder);
+        let t = a0.sub(builder, &a1).add(builder, &a2);
+        let s2 = t.square(builder);
+        let s3 = a1.mul(builder, &a2).double(builder);
+        let s4 = a2.square(builder);
+
+        let s3_nr = s3.mul_by_nonresidue(builder);
+        let c0 = s0.add(builder, &s3_nr);
+        let s4_nr = s4.mul_by_nonresidue(builder);
+        let c1 = s1.add(builder, &s4_nr);
+        let c2 = s1.add(builder, &s2);
+        let c2 = c2.add(builder, &s3);
+        let c2 = c2.sub(builder, &s0);
+        let c2 = c2.sub(builder, &s4);
+
+        Self::new(vec![
+            c0.coeffs[0].clone(),
+            c1.coeffs[0].clone(),
+            c2.coeffs[0].clone(),
+            c0.coeffs[1].clone(),
+            c1.coeffs[1].clone(),
+            c2.coeffs[1].clone(),
+        ])
     }
 
     pub fn mul_by_01(
@@ -277,6 +295,47 @@ impl<F: RichField + Extendable<D>, const D: usize> Fq6Target<F, D> {
         ])
     }
 
+    pub fn mul_by_12(
+        self,
+        builder: &mut CircuitBuilder<F, D>,
+        c1: &Fq2Target<F, D>,
+        c2: &Fq2Target<F, D>,
+    ) -> Self {
+        // (a0 + a1 v + a2 v^2) * (c1 v + c2 v^2), v^3 = ξ:
+        // c0' = (a1 * c2 + a2 * c1) * ξ
+        // c1' = a0 * c1 + a2 * c2 * ξ
+        // c2' = a0 * c2 + a1 * c1
+        let a0 = Fq2Target::new(vec![self.coeffs[0].clone(), self.coeffs[3].clone()]);
+        let a1 = Fq2Target::new(vec![self.coeffs[1].clone(), self.coeffs[4].clone()]);
+        let a2 = Fq2Target::new(vec![self.coeffs[2].clone(), self.coeffs[5].clone()]);
+
+        let a1_c1 = a1.mul(builder, c1);
+        let a2_c2 = a2.mul(builder, c2);
+
+        let a1_a2 = a1.add(builder, &a2);
+        let c1_c2 = c1.add(builder, c2);
+        let t0 = a1_a2.mul(builder, &c1_c2);
+        let t0 = t0.sub(builder, &a1_c1);
+        let t0 = t0.sub(builder, &a2_c2);
+        let t0 = t0.mul_by_nonresidue(builder);
+
+        let t1 = a0.mul(builder, c1);
+        let a2_c2_nr = a2_c2.mul_by_nonresidue(builder);
+        let t1 = t1.add(builder, &a2_c2_nr);
+
+        let t2 = a0.mul(builder, c2);
+        let t2 = t2.add(builder, &a1_c1);
+
+        Self::new(vec![
+            t0.coeffs[0].clone(),
+            t1.coeffs[0].clone(),
+            t2.coeffs[0].clone(),
+            t0.coeffs[1].clone(),
+            t1.coeffs[1].clone(),
+            t2.coeffs[1].clone(),
+        ])
+    }
+
     /// Multiply by quadratic nonresidue v.
     pub fn mul_by_nonresidue(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
         // Given a + bv + cv^2, this produces
@@ -414,6 +473,45 @@ mod tests {
     type C = PoseidonGoldilocksConfig;
     const D: usize = 2;
 
+    #[test]
+    fn test_fq6_mul_circuit() {
+        let rng = &mut rand::thread_rng();
+        let a = Fq6::rand(rng);
+        let b = Fq6::rand(rng);
+        let c_expected = a * b;
+
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let a_t = Fq6Target::constant(&mut builder, a);
+        let b_t = Fq6Target::constant(&mut builder, b);
+        let c_t = a_t.mul(&mut builder, &b_t);
+        let c_expected_t = Fq6Target::constant(&mut builder, c_expected);
+
+        Fq6Target::connect(&mut builder, &c_expected_t, &c_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_fq6_square_circuit() {
+        let rng = &mut rand::thread_rng();
+        let a = Fq6::rand(rng);
+
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let a_t = Fq6Target::constant(&mut builder, a);
+        let a_sq_t = a_t.square(&mut builder);
+        let a_sq_expected_t = Fq6Target::constant(&mut builder, a.square());
+
+        Fq6Target::connect(&mut builder, &a_sq_expected_t, &a_sq_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
     #[test]
     fn test_fq6_inv_circuit() {
         let rng = &mut rand::thread_rng();
@@ -1,6 +1,7 @@
 use std::marker::PhantomData;
 
 use ark_bls12_381::Fq;
+use ark_ff::{BigInteger, PrimeField};
 use itertools::Itertools;
 use num::{One, Zero};
 use num_bigint::BigUint;
@@ -13,17 +14,21 @@ use plonky2::{
         witness::{PartitionWitness, Witness, WitnessWrite},
     },
     plonk::circuit_builder::CircuitBuilder,
-    util::serialization::Buffer,
+    util::serialization::{Buffer, IoError, Read, Write},
 };
 use plonky2_ecdsa::gadgets::{
-    biguint::{BigUintTarget, GeneratedValuesBigUint, WitnessBigUint},
+    biguint::{BigUintTarget, CircuitBuilderBiguint, GeneratedValuesBigUint, WitnessBigUint},
     nonnative::{CircuitBuilderNonNative, NonNativeTarget},
 };
 use plonky2_u32::gadgets::{arithmetic_u32::U32Target, range_check::range_check_u32_circuit};
 
 use crate::{
+    curves::bits_to_biguint,
     fields::bls12_381base::Bls12_381Base,
-    utils::helpers::{from_biguint_to_fq, get_naf, sgn0_fq},
+    utils::{
+        helpers::{from_biguint_to_fq, get_naf, sgn0_fq},
+        serialization::{read_biguint_targets, write_biguint_targets},
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -142,7 +147,30 @@ impl<F: RichField + Extendable<D>, const D: usize> FqTarget<F, D> {
         Self::constant(builder, Fq::zero())
     }
 
+    /// Returns the value of `self` if all its limbs are circuit constants.
+    pub fn as_constant(&self, builder: &CircuitBuilder<F, D>) -> Option<Fq> {
+        let limbs = self
+            .target
+            .value
+            .limbs
+            .iter()
+            .map(|l| {
+                builder
+                    .target_as_constant(l.0)
+                    .map(|x| x.to_canonical_u64() as u32)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(from_biguint_to_fq(BigUint::from_slice(&limbs)))
+    }
+
+    // The arithmetic below is folded at build time when all operands are constants, e.g.
+    // the line coefficients of a constant G2 point evaluated at a constant G1 point. A
+    // product with a single constant operand is only folded when that constant is 0 or 1;
+    // `sum_of_products` saves the reductions of a sum of such products instead.
     pub fn add(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        if let (Some(a), Some(b)) = (self.as_constant(builder), rhs.as_constant(builder)) {
+            return Self::constant(builder, a + b);
+        }
         let target = builder.add_nonnative(&self.target, &rhs.target);
         Self {
             target,
@@ -151,6 +179,9 @@ impl<F: RichField + Extendable<D>, const D: usize> FqTarget<F, D> {
     }
 
     pub fn neg(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
+        if let Some(a) = self.as_constant(builder) {
+            return Self::constant(builder, -a);
+        }
         let target = builder.neg_nonnative(&self.target);
         Self {
             target,
@@ -159,6 +190,9 @@ impl<F: RichField + Extendable<D>, const D: usize> FqTarget<F, D> {
     }
 
     pub fn sub(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        if let (Some(a), Some(b)) = (self.as_constant(builder), rhs.as_constant(builder)) {
+            return Self::constant(builder, a - b);
+        }
         let target = builder.sub_nonnative(&self.target, &rhs.target);
         Self {
             target,
@@ -167,6 +201,13 @@ impl<F: RichField + Extendable<D>, const D: usize> FqTarget<F, D> {
     }
 
     pub fn mul(&self, builder: &mut CircuitBuilder<F, D>, rhs: &Self) -> Self {
+        match (self.as_constant(builder), rhs.as_constant(builder)) {
+            (Some(a), Some(b)) => return Self::constant(builder, a * b),
+            (Some(c), None) | (None, Some(c)) if c.is_zero() => return Self::zero(builder),
+            (Some(c), None) if c.is_one() => return rhs.clone(),
+            (None, Some(c)) if c.is_one() => return self.clone(),
+            _ => {}
+        }
         let target = builder.mul_nonnative(&self.target, &rhs.target);
         Self {
             target,
@@ -179,6 +220,41 @@ impl<F: RichField + Extendable<D>, const D: usize> FqTarget<F, D> {
         self.mul(builder, &c)
     }
 
+    /// Computes `Σ aᵢ·bᵢ` with a single reduction, instead of one per product and per sum.
+    /// Products with a constant 0 are skipped and products with a constant 1 are added as is.
+    pub fn sum_of_products(builder: &mut CircuitBuilder<F, D>, terms: &[(Self, Self)]) -> Self {
+        let mut constant = Fq::zero();
+        let mut acc: Option<BigUintTarget> = None;
+        for (a, b) in terms {
+            let x = match (a.as_constant(builder), b.as_constant(builder)) {
+                (Some(a), Some(b)) => {
+                    constant += a * b;
+                    continue;
+                }
+                (Some(c), None) | (None, Some(c)) if c.is_zero() => continue,
+                (Some(c), None) if c.is_one() => b.target.value.clone(),
+                (None, Some(c)) if c.is_one() => a.target.value.clone(),
+                _ => builder.mul_biguint(&a.target.value, &b.target.value),
+            };
+            acc = Some(match acc {
+                Some(acc) => builder.add_biguint(&acc, &x),
+                None => x,
+            });
+        }
+        let Some(mut acc) = acc else {
+            return Self::constant(builder, constant);
+        };
+        if !constant.is_zero() {
+            let c = builder.constant_biguint(&constant.into());
+            acc = builder.add_biguint(&acc, &c);
+        }
+        let target = builder.reduce(&acc);
+        Self {
+            target,
+            _marker: PhantomData,
+        }
+    }
+
     pub fn inv(&self, builder: &mut CircuitBuilder<F, D>) -> Self {
         let target = builder.inv_nonnative(&self.target);
         Self {
@@ -220,6 +296,74 @@ impl<F: RichField + Extendable<D>, const D: usize> FqTarget<F, D> {
         sqrt
     }
 
+    /// The `sqrt_ratio` of RFC 9380 for a non-square `z`: returns `(true, sqrt(self / v))` if
+    /// `self / v` is square, and `(false, sqrt(z * self / v))` otherwise. The root is supplied
+    /// by a generator and is canonical but of either sign. `v` must be non-zero.
+    pub fn sqrt_ratio(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        v: &Self,
+        z: Fq,
+    ) -> (BoolTarget, Self) {
+        let is_qr = builder.add_virtual_bool_target_safe();
+        let y = Self::empty(builder);
+        builder.add_simple_generator(FqSqrtRatioGenerator::<F, D> {
+            u: self.target.value.clone(),
+            v: v.target.value.clone(),
+            z,
+            is_qr,
+            y: y.target.value.clone(),
+            _marker: PhantomData,
+        });
+        y.assert_canonical(builder);
+
+        // y^2 * v = u or z * u
+        let y_sq = y.mul(builder, &y);
+        let y_sq_v = y_sq.mul(builder, v);
+        let z_u = self.mul_const(builder, &z);
+        let expected = Self::select(builder, self, &z_u, &is_qr);
+        Self::connect(builder, &y_sq_v, &expected);
+
+        // both cases hold for u = 0, which is a square
+        let is_u_zero = self.is_zero(builder);
+        let not_qr_at_zero = builder.mul_sub(is_u_zero.target, is_qr.target, is_u_zero.target);
+        builder.assert_zero(not_qr_at_zero);
+
+        (is_qr, y)
+    }
+
+    /// Returns whether `self > (p - 1) / 2`, the sign convention of the Zcash point
+    /// encodings. `self` must be canonical, see [`Self::assert_canonical`].
+    pub fn is_lexicographically_largest(&self, builder: &mut CircuitBuilder<F, D>) -> BoolTarget {
+        let half: BigUint = (-Fq::one() / Fq::from(2)).into();
+        let half = builder.constant_biguint(&half);
+        let is_at_most_half = builder.cmp_biguint(&self.target.value, &half);
+        builder.not(is_at_most_half)
+    }
+
+    /// Asserts that the limbs of `self` encode an integer below `p`.
+    pub fn assert_canonical(&self, builder: &mut CircuitBuilder<F, D>) {
+        let p_minus_one: BigUint = (-Fq::one()).into();
+        let p_minus_one = builder.constant_biguint(&p_minus_one);
+        let is_canonical = builder.cmp_biguint(&self.target.value, &p_minus_one);
+        builder.assert_one(is_canonical.target);
+    }
+
+    /// Builds an element from at most 384 little-endian bits, rejecting values that are not
+    /// below `p`.
+    pub fn from_bits(builder: &mut CircuitBuilder<F, D>, bits: &[BoolTarget]) -> Self {
+        assert!(bits.len() <= 12 * 32);
+        let mut x = bits_to_biguint(builder, bits);
+        x.limbs.resize(12, U32Target(builder.zero()));
+        let target = builder.biguint_to_nonnative(&x);
+        let x = Self {
+            target,
+            _marker: PhantomData,
+        };
+        x.assert_canonical(builder);
+        x
+    }
+
     // TODO! have to consider self = zero case
     pub fn pow(&self, builder: &mut CircuitBuilder<F, D>, exp: Vec<u64>) -> Self {
         let a = self.clone();
@@ -346,22 +490,100 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for FqS
     }
 }
 
+#[derive(Debug)]
+struct FqSqrtRatioGenerator<F: RichField + Extendable<D>, const D: usize> {
+    u: BigUintTarget,
+    v: BigUintTarget,
+    z: Fq,
+    is_qr: BoolTarget,
+    y: BigUintTarget,
+    _marker: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for FqSqrtRatioGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.u
+            .limbs
+            .iter()
+            .chain(self.v.limbs.iter())
+            .map(|&l| l.0)
+            .collect_vec()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        use ark_ff::Field as ArkField;
+        let u = from_biguint_to_fq(witness.get_biguint_target(self.u.clone()));
+        let v = from_biguint_to_fq(witness.get_biguint_target(self.v.clone()));
+        let ratio = u * v.inverse().unwrap();
+        let (is_qr, y) = match ratio.sqrt() {
+            Some(y) => (true, y),
+            None => (false, (self.z * ratio).sqrt().unwrap()),
+        };
+        out_buffer.set_bool_target(self.is_qr, is_qr);
+        let y_biguint: BigUint = y.into();
+        out_buffer.set_biguint_target(&self.y, &y_biguint);
+    }
+
+    fn id(&self) -> std::string::String {
+        "FqSqrtRatioGenerator".to_string()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        write_biguint_targets(dst, &[self.u.clone(), self.v.clone(), self.y.clone()])?;
+        dst.write_all(&self.z.into_bigint().to_bytes_le())?;
+        dst.write_target_bool(self.is_qr)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        let [u, v, y]: [BigUintTarget; 3] =
+            read_biguint_targets(src)?.try_into().map_err(|_| IoError)?;
+        let mut z = [0u8; 48];
+        src.read_exact(&mut z)?;
+        Ok(Self {
+            u,
+            v,
+            z: Fq::from_le_bytes_mod_order(&z),
+            is_qr: src.read_target_bool()?,
+            y,
+            _marker: PhantomData,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::marker::PhantomData;
+
     use ark_bls12_381::Fq;
     use ark_ff::Field;
     use ark_std::{UniformRand, Zero};
     use plonky2::{
         field::{goldilocks_field::GoldilocksField, types::Field as Plonky2Field},
-        iop::witness::{PartialWitness, WitnessWrite},
+        iop::{
+            generator::SimpleGenerator,
+            witness::{PartialWitness, WitnessWrite},
+        },
         plonk::{
             circuit_builder::CircuitBuilder, circuit_data::CircuitConfig,
             config::PoseidonGoldilocksConfig,
         },
+        util::serialization::Buffer,
     };
     use rand::Rng;
 
-    use super::FqTarget;
+    use super::{FqSqrtRatioGenerator, FqTarget};
     use crate::utils::helpers::sgn0_fq;
 
     type F = GoldilocksField;
@@ -525,4 +747,32 @@ mod tests {
         let data = builder.build::<C>();
         let _proof = data.prove(pw);
     }
+
+    #[test]
+    fn test_sqrt_ratio_generator_serialization() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let u_t = FqTarget::empty(&mut builder);
+        let v_t = FqTarget::empty(&mut builder);
+        let y_t = FqTarget::empty(&mut builder);
+        let generator = FqSqrtRatioGenerator::<F, D> {
+            u: u_t.target.value,
+            v: v_t.target.value,
+            z: Fq::from(11),
+            is_qr: builder.add_virtual_bool_target_safe(),
+            y: y_t.target.value,
+            _marker: PhantomData,
+        };
+        let data = builder.build::<C>();
+
+        let mut bytes = vec![];
+        generator.serialize(&mut bytes, &data.common).unwrap();
+        let decoded =
+            FqSqrtRatioGenerator::<F, D>::deserialize(&mut Buffer::new(&bytes), &data.common)
+                .unwrap();
+        assert_eq!(decoded.dependencies(), generator.dependencies());
+        assert_eq!(decoded.z, generator.z);
+        assert_eq!(decoded.is_qr, generator.is_qr);
+        assert_eq!(decoded.y.limbs, generator.y.limbs);
+    }
 }
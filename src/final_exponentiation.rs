@@ -1,12 +1,33 @@
+use std::marker::PhantomData;
+
+use ark_bls12_381::{Fq, Fq12};
+use itertools::Itertools;
+use num_bigint::BigUint;
 use plonky2::{
-    field::extension::Extendable, hash::hash_types::RichField,
+    field::extension::Extendable,
+    hash::hash_types::RichField,
+    iop::{
+        generator::{GeneratedValues, SimpleGenerator},
+        target::Target,
+        witness::PartitionWitness,
+    },
     plonk::circuit_builder::CircuitBuilder,
+    util::serialization::Buffer,
 };
+use plonky2_ecdsa::gadgets::biguint::{BigUintTarget, GeneratedValuesBigUint, WitnessBigUint};
 
 use crate::{
-    fields::fq12_target::Fq12Target,
-    final_exponentiation_helpers::{frobenius_map, pow_target},
-    utils::constants::BLS_X,
+    fields::{fq12_target::Fq12Target, fq6_target::Fq6Target, fq_target::FqTarget},
+    final_exponentiation_helpers::{
+        cyclotomic_exp_by_x, frobenius_map, karabina_exp_by_x, pow_target,
+    },
+    native::final_exponentiation::residue_witness,
+    utils::{
+        constants::BLS_X,
+        helpers::{from_biguint_to_fq, MyFq12},
+        my_fq6::MyFq6,
+        serialization::{read_biguint_targets, write_biguint_targets},
+    },
 };
 
 pub fn easy_part<F: RichField + Extendable<D>, const D: usize>(
@@ -20,30 +41,48 @@ pub fn easy_part<F: RichField + Extendable<D>, const D: usize>(
     f3.mul(builder, &f2)
 }
 
+/// How the exponentiations by `x` in the hard part are computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExpByXStrategy {
+    /// Granger–Scott squaring of the full element.
+    #[default]
+    Cyclotomic,
+    /// Karabina's compressed squaring with batched decompression.
+    Karabina,
+}
+
 pub fn hard_part_target<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     r: Fq12Target<F, D>,
 ) -> Fq12Target<F, D> {
-    let mut y0 = r.mul(builder, &r); // optimize square
-    let mut y1 = pow_target(builder, r.clone(), vec![BLS_X]);
-    y1 = y1.conjugate(builder);
+    hard_part_target_with_strategy(builder, r, ExpByXStrategy::default())
+}
+
+pub fn hard_part_target_with_strategy<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    r: Fq12Target<F, D>,
+    strategy: ExpByXStrategy,
+) -> Fq12Target<F, D> {
+    let exp_by_x = match strategy {
+        ExpByXStrategy::Cyclotomic => cyclotomic_exp_by_x::<F, D>,
+        ExpByXStrategy::Karabina => karabina_exp_by_x::<F, D>,
+    };
+
+    let mut y0 = r.cyclotomic_square(builder);
+    let mut y1 = exp_by_x(builder, &r);
     let mut y2 = r.clone();
     y2 = y2.conjugate(builder);
 
     y1 = y1.mul(builder, &y2);
-    y2 = pow_target(builder, y1.clone(), vec![BLS_X]);
-    y2 = y2.conjugate(builder);
+    y2 = exp_by_x(builder, &y1);
     y1 = y1.conjugate(builder);
     y1 = y1.mul(builder, &y2);
-    y2 = pow_target(builder, y1.clone(), vec![BLS_X]);
-    y2 = y2.conjugate(builder);
+    y2 = exp_by_x(builder, &y1);
     y1 = frobenius_map(builder, &y1, 1);
     y1 = y1.mul(builder, &y2);
     let r = r.mul(builder, &y0);
-    y0 = pow_target(builder, y1.clone(), vec![BLS_X]);
-    y0 = y0.conjugate(builder);
-    y2 = pow_target(builder, y0, vec![BLS_X]);
-    y2 = y2.conjugate(builder);
+    y0 = exp_by_x(builder, &y1);
+    y2 = exp_by_x(builder, &y0);
     y0 = y1.clone();
     y0 = frobenius_map(builder, &y0, 2);
     y1 = y1.conjugate(builder);
@@ -62,23 +101,140 @@ pub fn final_exponentiation<F: RichField + Extendable<D>, const D: usize>(
     hard_part_target(builder, f0)
 }
 
+/// Asserts that `a^((p^12 - 1) / r) = 1` without computing the final exponentiation.
+/// A generator supplies a residue witness `c` and a scaling factor `s ∈ Fq6`, and the
+/// circuit only checks `a * s = c^(p - x)`, see <https://eprint.iacr.org/2024/640.pdf>.
+pub fn assert_final_exponentiation_is_one<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &Fq12Target<F, D>,
+) {
+    let c = Fq12Target::empty(builder);
+    let s = Fq6Target::empty(builder);
+    let limbs =
+        |coeffs: &[FqTarget<F, D>]| coeffs.iter().map(|x| x.target.value.clone()).collect_vec();
+    builder.add_simple_generator(ResidueWitnessGenerator::<F, D> {
+        f: limbs(&a.coeffs),
+        c: limbs(&c.coeffs),
+        s: limbs(&s.coeffs),
+        _marker: PhantomData,
+    });
+
+    // s must be non-zero, since every element of Fq6* has order dividing (p^12 - 1) / r
+    s.inv(builder);
+
+    let (a_c0, a_c1) = a.convert_to_fq6();
+    let a_c0 = a_c0.mul(builder, &s);
+    let a_c1 = a_c1.mul(builder, &s);
+    let lhs = Fq12Target::from_fq6(a_c0, a_c1);
+
+    // c^(p - x) = c^p * c^|x|, since x is negative
+    let c_p = frobenius_map(builder, &c, 1);
+    let c_x = pow_target(builder, c, vec![BLS_X]);
+    let rhs = c_p.mul(builder, &c_x);
+
+    Fq12Target::connect(builder, &lhs, &rhs);
+}
+
+#[derive(Debug)]
+struct ResidueWitnessGenerator<F: RichField + Extendable<D>, const D: usize> {
+    f: Vec<BigUintTarget>,
+    c: Vec<BigUintTarget>,
+    s: Vec<BigUintTarget>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for ResidueWitnessGenerator<F, D>
+{
+    fn dependencies(&self) -> Vec<Target> {
+        self.f
+            .iter()
+            .flat_map(|coeff| coeff.limbs.iter().map(|&l| l.0))
+            .collect_vec()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let coeffs: Vec<Fq> = self
+            .f
+            .iter()
+            .map(|x| from_biguint_to_fq(witness.get_biguint_target(x.clone())))
+            .collect_vec();
+        let f = MyFq12 {
+            coeffs: coeffs.try_into().unwrap(),
+        };
+        let f: Fq12 = f.into();
+        let (c, s) = residue_witness(f);
+
+        let c: MyFq12 = c.into();
+        let c_biguint: Vec<BigUint> = c.coeffs.iter().cloned().map(|x| x.into()).collect_vec();
+        for (c_t, c) in self.c.iter().zip(c_biguint) {
+            out_buffer.set_biguint_target(c_t, &c);
+        }
+
+        let s: MyFq6 = s.into();
+        let s_biguint: Vec<BigUint> = s.coeffs.iter().cloned().map(|x| x.into()).collect_vec();
+        for (s_t, s) in self.s.iter().zip(s_biguint) {
+            out_buffer.set_biguint_target(s_t, &s);
+        }
+    }
+
+    fn id(&self) -> std::string::String {
+        "ResidueWitnessGenerator".to_string()
+    }
+
+    fn serialize(
+        &self,
+        dst: &mut Vec<u8>,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<()> {
+        write_biguint_targets(dst, &self.f)?;
+        write_biguint_targets(dst, &self.c)?;
+        write_biguint_targets(dst, &self.s)
+    }
+
+    fn deserialize(
+        src: &mut Buffer,
+        _common_data: &plonky2::plonk::circuit_data::CommonCircuitData<F, D>,
+    ) -> plonky2::util::serialization::IoResult<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            f: read_biguint_targets(src)?,
+            c: read_biguint_targets(src)?,
+            s: read_biguint_targets(src)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::time::Instant;
+    use std::{marker::PhantomData, time::Instant};
 
-    use ark_bls12_381::Fq12;
-    use ark_ec::pairing::{MillerLoopOutput, Pairing};
+    use ark_bls12_381::{Fq12, Fr, G1Affine, G2Affine};
+    use ark_ec::{
+        pairing::{MillerLoopOutput, Pairing},
+        CurveGroup,
+    };
     use ark_ff::UniformRand;
     use plonky2::{
         field::goldilocks_field::GoldilocksField,
-        iop::witness::PartialWitness,
+        iop::{generator::SimpleGenerator, witness::PartialWitness},
         plonk::{
             circuit_builder::CircuitBuilder, circuit_data::CircuitConfig,
             config::PoseidonGoldilocksConfig,
         },
+        util::serialization::Buffer,
     };
 
-    use crate::{fields::fq12_target::Fq12Target, final_exponentiation::final_exponentiation};
+    use crate::{
+        fields::{fq12_target::Fq12Target, fq6_target::Fq6Target, fq_target::FqTarget},
+        final_exponentiation::{
+            assert_final_exponentiation_is_one, easy_part, final_exponentiation,
+            hard_part_target_with_strategy, ExpByXStrategy, ResidueWitnessGenerator,
+        },
+    };
 
     type F = GoldilocksField;
     type C = PoseidonGoldilocksConfig;
@@ -106,4 +262,96 @@ mod tests {
         let _proof = data.prove(pw);
         println!("time: {:?}", now.elapsed());
     }
+
+    #[test]
+    fn test_hard_part_karabina() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let x = Fq12::rand(rng);
+        let input_t = Fq12Target::constant(&mut builder, x);
+
+        let easy_t = easy_part(&mut builder, &input_t);
+        let output = hard_part_target_with_strategy(&mut builder, easy_t, ExpByXStrategy::Karabina);
+        let output_expected = ark_bls12_381::Bls12_381::final_exponentiation(MillerLoopOutput(x))
+            .unwrap()
+            .0;
+        let output_expected_t = Fq12Target::constant(&mut builder, output_expected);
+
+        Fq12Target::connect(&mut builder, &output, &output_expected_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_assert_final_exponentiation_is_one() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let s = Fr::rand(rng);
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let sp = (p * s).into_affine();
+        let sq = (q * s).into_affine();
+        let x = ark_bls12_381::Bls12_381::multi_miller_loop([sp, -p], [q, sq]).0;
+        let input_t = Fq12Target::constant(&mut builder, x);
+
+        assert_final_exponentiation_is_one(&mut builder, &input_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_final_exponentiation_is_one_fail() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let x = Fq12::rand(rng);
+        let input_t = Fq12Target::constant(&mut builder, x);
+
+        assert_final_exponentiation_is_one(&mut builder, &input_t);
+
+        let pw = PartialWitness::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_residue_witness_generator_serialization() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let limbs = |coeffs: &[FqTarget<F, D>]| {
+            coeffs
+                .iter()
+                .map(|x| x.target.value.clone())
+                .collect::<Vec<_>>()
+        };
+        let generator = ResidueWitnessGenerator::<F, D> {
+            f: limbs(&Fq12Target::empty(&mut builder).coeffs),
+            c: limbs(&Fq12Target::empty(&mut builder).coeffs),
+            s: limbs(&Fq6Target::empty(&mut builder).coeffs),
+            _marker: PhantomData,
+        };
+        let data = builder.build::<C>();
+
+        let mut bytes = vec![];
+        generator.serialize(&mut bytes, &data.common).unwrap();
+        let decoded =
+            ResidueWitnessGenerator::<F, D>::deserialize(&mut Buffer::new(&bytes), &data.common)
+                .unwrap();
+        assert_eq!(decoded.dependencies(), generator.dependencies());
+        for (a, b) in decoded
+            .c
+            .iter()
+            .chain(&decoded.s)
+            .zip(generator.c.iter().chain(&generator.s))
+        {
+            assert_eq!(a.limbs, b.limbs);
+        }
+    }
 }
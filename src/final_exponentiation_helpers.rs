@@ -10,7 +10,13 @@ use plonky2::{
     plonk::circuit_builder::CircuitBuilder,
 };
 
-use crate::fields::{fq12_target::Fq12Target, fq2_target::Fq2Target};
+use crate::{
+    fields::{
+        fq12_target::{CompressedFq12Target, Fq12Target},
+        fq2_target::Fq2Target,
+    },
+    utils::constants::{BLS_X, BLS_X_IS_NEGATIVE},
+};
 
 pub fn frobenius_map<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
@@ -68,7 +74,7 @@ pub fn pow_target<F: RichField + Extendable<D>, const D: usize>(
 
     for &z in naf.iter().rev() {
         if is_started {
-            res = res.mul(builder, &res);
+            res = res.square(builder);
         }
 
         if z != 0 {
@@ -85,6 +91,66 @@ pub fn pow_target<F: RichField + Extendable<D>, const D: usize>(
     res
 }
 
+/// Computes `a^x` for `a` in the cyclotomic subgroup, where `x` is the BLS parameter.
+pub fn cyclotomic_exp_by_x<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &Fq12Target<F, D>,
+) -> Fq12Target<F, D> {
+    let mut res = a.clone();
+    let mut is_started = false;
+    let naf = get_bls_naf(vec![BLS_X]);
+
+    for &z in naf.iter().rev() {
+        if is_started {
+            res = res.cyclotomic_square(builder);
+        }
+
+        if z != 0 {
+            assert!(z == 1 || z == -1);
+            if is_started {
+                res = res.mul(builder, a);
+            } else {
+                assert_eq!(z, 1);
+                is_started = true;
+            }
+        }
+    }
+
+    // the inverse of a cyclotomic element is its conjugate
+    if BLS_X_IS_NEGATIVE {
+        res.conjugate(builder)
+    } else {
+        res
+    }
+}
+
+/// Computes `a^x` for `a` in the cyclotomic subgroup with Karabina's compressed squaring.
+/// `a^(2^i)` is only decompressed for the set bits `i` of `|x|`, all in one batch.
+pub fn karabina_exp_by_x<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &Fq12Target<F, D>,
+) -> Fq12Target<F, D> {
+    let mut compressed = a.compress();
+    let mut to_decompress = Vec::with_capacity(BLS_X.count_ones() as usize);
+    for i in 1..64 - BLS_X.leading_zeros() {
+        compressed = compressed.square(builder);
+        if (BLS_X >> i) & 1 == 1 {
+            to_decompress.push(compressed.clone());
+        }
+    }
+    let decompressed = CompressedFq12Target::decompress_batch(builder, &to_decompress);
+
+    let lsb = (BLS_X & 1 == 1).then(|| a.clone());
+    let res = Fq12Target::multiply_elements(builder, lsb.into_iter().chain(decompressed)).unwrap();
+
+    // the inverse of a cyclotomic element is its conjugate
+    if BLS_X_IS_NEGATIVE {
+        res.conjugate(builder)
+    } else {
+        res
+    }
+}
+
 pub fn get_bls_naf(mut exp: Vec<u64>) -> Vec<i8> {
     // https://en.wikipedia.org/wiki/Non-adjacent_form
     // NAF for exp:
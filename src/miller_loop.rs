@@ -1,7 +1,7 @@
-use ark_bls12_381::Fq12;
-use ark_ff::BitIteratorBE;
-use ark_std::cfg_chunks_mut;
-use num::One;
+use ark_bls12_381::{Fq12, Fq2, Fq6};
+use ark_ff::{AdditiveGroup, BitIteratorBE};
+use itertools::Itertools;
+use num::{One, Zero};
 use plonky2::{
     field::extension::Extendable, hash::hash_types::RichField,
     plonk::circuit_builder::CircuitBuilder,
@@ -9,10 +9,11 @@ use plonky2::{
 
 use crate::{
     curves::{
+        bool_as_constant,
         g1::{G1AffineTarget, G1PreparedTarget},
         g2::{EllCoeffTarget, G2PreparedTarget},
     },
-    fields::fq12_target::Fq12Target,
+    fields::{fq12_target::Fq12Target, fq2_target::Fq2Target, fq_target::FqTarget},
     utils::constants::{BLS_X, BLS_X_IS_NEGATIVE},
 };
 
@@ -21,37 +22,43 @@ pub fn multi_miller_loop<F: RichField + Extendable<D>, const D: usize>(
     a: impl IntoIterator<Item = impl Into<G1PreparedTarget<F, D>>>,
     b: impl IntoIterator<Item = impl Into<G2PreparedTarget<F, D>>>,
 ) -> Fq12Target<F, D> {
-    use itertools::Itertools;
-
     let mut pairs = a
         .into_iter()
         .zip_eq(b)
         .filter_map(|(p, q)| {
-            let (p, q) = (p.into(), q.into());
-            match !p.0.is_zero() && !q.is_zero() {
-                true => Some((p, q.ell_coeffs.into_iter())),
-                false => None,
-            }
+            let (p, q): (G1PreparedTarget<F, D>, G2PreparedTarget<F, D>) = (p.into(), q.into());
+            // pairs known to contain the identity are dropped, the others are selected out
+            // in `line_target` when either flag is set
+            let infinity = match (
+                bool_as_constant(builder, p.0.is_zero()),
+                bool_as_constant(builder, q.is_zero()),
+            ) {
+                (Some(true), _) | (_, Some(true)) => return None,
+                (Some(false), _) => q.is_zero(),
+                (_, Some(false)) => p.0.is_zero(),
+                (None, None) => builder.or(p.0.is_zero(), q.is_zero()),
+            };
+            let p = G1PreparedTarget(G1AffineTarget { infinity, ..p.0 });
+            Some((p, q.ell_coeffs.into_iter()))
         })
         .collect::<Vec<_>>();
-    let mut pairs_f_storage: Vec<Fq12Target<F, D>> = Vec::new();
-
-    for pairs in cfg_chunks_mut!(pairs, 4) {
-        let mut f = Fq12Target::constant(builder, Fq12::one());
-        for i in BitIteratorBE::without_leading_zeros([BLS_X]).skip(1) {
-            f = f.mul(builder, &f);
-            for (p, coeffs) in pairs.iter_mut() {
-                f = ell_target(builder, &f, coeffs.next().unwrap(), p.0.clone());
-            }
-            if i {
-                for (p, coeffs) in pairs.iter_mut() {
-                    f = ell_target(builder, &f, coeffs.next().unwrap(), p.0.clone());
-                }
-            }
+
+    let mut f = Fq12Target::constant(builder, Fq12::one());
+    for i in BitIteratorBE::without_leading_zeros([BLS_X]).skip(1) {
+        f = f.square(builder);
+        let lines = pairs
+            .iter_mut()
+            .map(|(p, coeffs)| (coeffs.next().unwrap(), p.0.clone()))
+            .collect_vec();
+        f = mul_by_lines(builder, &f, lines);
+        if i {
+            let lines = pairs
+                .iter_mut()
+                .map(|(p, coeffs)| (coeffs.next().unwrap(), p.0.clone()))
+                .collect_vec();
+            f = mul_by_lines(builder, &f, lines);
         }
-        pairs_f_storage.push(f)
     }
-    let mut f = Fq12Target::multiply_elements(builder, pairs_f_storage.into_iter()).unwrap();
 
     if BLS_X_IS_NEGATIVE {
         f = f.conjugate(builder);
@@ -60,21 +67,121 @@ pub fn multi_miller_loop<F: RichField + Extendable<D>, const D: usize>(
     f
 }
 
+/// Multiplies `f` by a line evaluated at `p`. When the line coefficients are constants but `p`
+/// is not, `f * (c0 + c1 px v + c2 py v w)` is expanded as `f c0 + px (f c1 v) + py (f c2 v w)`,
+/// so each coefficient of the product is one sum of products reduced once, with `f c1 v` and
+/// `f c2 v w` multiplied by constants.
 fn ell_target<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     f: &Fq12Target<F, D>,
     g2_coeffs: EllCoeffTarget<F, D>,
     p: G1AffineTarget<F, D>,
 ) -> Fq12Target<F, D> {
+    let (px, py) = p.xy();
+    let p_is_constant = px.as_constant(builder).is_some() && py.as_constant(builder).is_some();
+    let Some([c0, c1, c2]) = ell_coeffs_as_constant(builder, &g2_coeffs).filter(|_| !p_is_constant)
+    else {
+        let [c0, c1, c2] = line_target(builder, g2_coeffs, p);
+        return f.mul_by_014(builder, &c0, &c1, &c2);
+    };
+
+    let sparse = |c0, c1, c4| {
+        Fq12::new(
+            Fq6::new(c0, c1, Fq2::ZERO),
+            Fq6::new(Fq2::ZERO, c4, Fq2::ZERO),
+        )
+    };
+    let f_c0 = f.mul_const_terms(builder, &sparse(c0, Fq2::ZERO, Fq2::ZERO));
+    let f_c1 = f.mul_const(builder, &sparse(Fq2::ZERO, c1, Fq2::ZERO));
+    let f_c2 = f.mul_const(builder, &sparse(Fq2::ZERO, Fq2::ZERO, c2));
+    let coeffs = f_c0
+        .into_iter()
+        .zip_eq(f_c1.coeffs)
+        .zip_eq(f_c2.coeffs)
+        .map(|((mut terms, x1), x2)| {
+            terms.push((x1, px.clone()));
+            terms.push((x2, py.clone()));
+            FqTarget::sum_of_products(builder, &terms)
+        })
+        .collect_vec();
+    let r = Fq12Target::new(coeffs);
+
+    // the line is one when `p` is at infinity
+    match bool_as_constant(builder, p.is_zero()) {
+        Some(false) => r,
+        _ => Fq12Target::select(builder, f, &r, &p.infinity),
+    }
+}
+
+/// Returns the line coefficients if they are all circuit constants, as for a point prepared with
+/// [`G2PreparedTarget::from_constant`].
+fn ell_coeffs_as_constant<F: RichField + Extendable<D>, const D: usize>(
+    builder: &CircuitBuilder<F, D>,
+    g2_coeffs: &EllCoeffTarget<F, D>,
+) -> Option<[Fq2; 3]> {
+    Some([
+        g2_coeffs.0.as_constant(builder)?,
+        g2_coeffs.1.as_constant(builder)?,
+        g2_coeffs.2.as_constant(builder)?,
+    ])
+}
+
+/// Evaluates a line at `p`, giving the coefficients of a sparse element at positions 0, 1 and 4.
+/// The line is replaced by one when `p` is at infinity.
+fn line_target<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    g2_coeffs: EllCoeffTarget<F, D>,
+    p: G1AffineTarget<F, D>,
+) -> [Fq2Target<F, D>; 3] {
     let c0 = g2_coeffs.0;
     let c1 = g2_coeffs.1;
     let c2 = g2_coeffs.2;
-    let (px, py) = p.xy().unwrap();
+    let (px, py) = p.xy();
 
     let c2 = c2.mul_assign_by_fp(builder, py.clone());
     let c1 = c1.mul_assign_by_fp(builder, px.clone());
 
-    f.mul_by_014(builder, &c0, &c1, &c2)
+    if bool_as_constant(builder, p.is_zero()) == Some(false) {
+        return [c0, c1, c2];
+    }
+    let one = Fq2Target::constant(builder, Fq2::one());
+    let zero = Fq2Target::constant(builder, Fq2::zero());
+    [
+        Fq2Target::select(builder, &one, &c0, &p.infinity),
+        Fq2Target::select(builder, &zero, &c1, &p.infinity),
+        Fq2Target::select(builder, &zero, &c2, &p.infinity),
+    ]
+}
+
+/// Multiplies `f` by the lines evaluated at their points. Lines with constant coefficients are
+/// multiplied one at a time by [`ell_target`], the others are combined pairwise with
+/// [`Fq12Target::mul_014_by_014`] first.
+fn mul_by_lines<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    f: &Fq12Target<F, D>,
+    lines: Vec<(EllCoeffTarget<F, D>, G1AffineTarget<F, D>)>,
+) -> Fq12Target<F, D> {
+    let (constant_lines, lines): (Vec<_>, Vec<_>) = lines
+        .into_iter()
+        .partition(|(coeffs, _)| ell_coeffs_as_constant(builder, coeffs).is_some());
+    let mut f = f.clone();
+    for (coeffs, p) in constant_lines {
+        f = ell_target(builder, &f, coeffs, p);
+    }
+    for mut chunk in &lines.into_iter().chunks(2) {
+        let (a_coeffs, a_p) = chunk.next().unwrap();
+        f = match chunk.next() {
+            Some((b_coeffs, b_p)) => {
+                let a = line_target(builder, a_coeffs, a_p);
+                let b = line_target(builder, b_coeffs, b_p);
+                let ab = Fq12Target::mul_014_by_014(builder, &a, &b);
+                f.mul_by_01245(builder, &ab)
+            }
+            None => ell_target(builder, &f, a_coeffs, a_p),
+        };
+    }
+
+    f
 }
 
 #[cfg(test)]
@@ -98,6 +205,7 @@ mod tests {
             g2::{G2AffineTarget, G2PreparedTarget},
         },
         fields::{fq12_target::Fq12Target, fq2_target::Fq2Target},
+        final_exponentiation::final_exponentiation,
         miller_loop::multi_miller_loop,
         native::miller_loop::ell,
     };
@@ -130,6 +238,112 @@ mod tests {
         let _proof = data.prove(pw);
     }
 
+    #[test]
+    fn test_miller_loop_constant_g2() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let r_expected = ark_bls12_381::Bls12_381::miller_loop(p, q).0;
+
+        let p_prepared_t = [G1PreparedTarget(G1AffineTarget::constant(&mut builder, p))];
+        let q_prepared_t = [G2PreparedTarget::from_constant(&mut builder, q)];
+
+        let r_t = multi_miller_loop(&mut builder, p_prepared_t, q_prepared_t);
+
+        let r_expected_t = Fq12Target::constant(&mut builder, r_expected);
+
+        Fq12Target::connect(&mut builder, &r_t, &r_expected_t);
+
+        let pw = PartialWitness::<F>::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_miller_loop_constant_g2_gate_count() {
+        let rng = &mut rand::thread_rng();
+        let q = G2Affine::rand(rng);
+        let num_gates = |constant_g2: bool| {
+            let config = CircuitConfig::wide_ecc_config();
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+            let p_t = G1AffineTarget::add_virtual(&mut builder);
+            let q_prepared_t = match constant_g2 {
+                true => G2PreparedTarget::from_constant(&mut builder, q),
+                false => {
+                    let q_t = G2AffineTarget::add_virtual(&mut builder);
+                    G2PreparedTarget::from(&mut builder, q_t)
+                }
+            };
+            multi_miller_loop(&mut builder, [G1PreparedTarget(p_t)], [q_prepared_t]);
+            builder.num_gates()
+        };
+        // the preparation of the point is saved and the lines are multiplied as constants
+        assert!(num_gates(true) < num_gates(false));
+    }
+
+    #[test]
+    fn test_miller_loop_line_witness() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let r_expected = ark_bls12_381::Bls12_381::pairing(p, q).0;
+
+        let p_prepared_t = [G1PreparedTarget(G1AffineTarget::constant(&mut builder, p))];
+        let q_t = G2AffineTarget::constant(&mut builder, q);
+        let q_prepared_t = [G2PreparedTarget::from_line_witness(&mut builder, q_t)];
+
+        // the lines are scaled by Fq2 factors, so only the pairing matches
+        let f_t = multi_miller_loop(&mut builder, p_prepared_t, q_prepared_t);
+        let r_t = final_exponentiation(&mut builder, f_t);
+
+        let r_expected_t = Fq12Target::constant(&mut builder, r_expected);
+
+        Fq12Target::connect(&mut builder, &r_t, &r_expected_t);
+
+        let pw = PartialWitness::<F>::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_miller_loop_identity_witness() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let q = G2Affine::rand(rng);
+        let p2 = G1Affine::rand(rng);
+        let q2 = G2Affine::rand(rng);
+
+        // the same circuit handles a witness point that may be the identity
+        let p_t = G1AffineTarget::add_virtual(&mut builder);
+        let p_prepared_t = [
+            G1PreparedTarget(p_t.clone()),
+            G1PreparedTarget(G1AffineTarget::constant(&mut builder, p2)),
+        ];
+        let q_prepared_t = [
+            G2PreparedTarget::from_constant(&mut builder, q),
+            G2PreparedTarget::from_constant(&mut builder, q2),
+        ];
+        let r_t = multi_miller_loop(&mut builder, p_prepared_t, q_prepared_t);
+
+        let r_expected_t = builder.add_virtual_targets(12 * 12);
+        let r_expected_t = Fq12Target::from_vec(&mut builder, &r_expected_t);
+        Fq12Target::connect(&mut builder, &r_t, &r_expected_t);
+
+        let data = builder.build::<C>();
+        for p in [G1Affine::identity(), G1Affine::rand(rng)] {
+            let r_expected = ark_bls12_381::Bls12_381::multi_miller_loop([p, p2], [q, q2]).0;
+            let mut pw = PartialWitness::<F>::new();
+            p_t.set_witness(&mut pw, &p);
+            r_expected_t.set_witness(&mut pw, &r_expected);
+            let _proof = data.prove(pw).unwrap();
+        }
+    }
+
     #[test]
     fn test_ell_target() {
         let config = CircuitConfig::wide_ecc_config();
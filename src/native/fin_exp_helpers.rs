@@ -1,11 +1,14 @@
 use std::ops::Div;
 
-use ark_bls12_381::{Fq, Fq2};
-use ark_ff::{Field, Fp2};
+use ark_bls12_381::{Fq, Fq2, Fq6Config};
+use ark_ff::{AdditiveGroup, Field, Fp2, Fp6Config};
 use itertools::Itertools;
-use num::{BigUint, One, Zero};
+use num::{BigInt, BigUint, Integer, One, Zero};
 
-use crate::utils::helpers::MyFq12;
+use crate::utils::{
+    constants::{BLS_X, BLS_X_IS_NEGATIVE},
+    helpers::MyFq12,
+};
 
 pub fn frobenius_map_native(a: MyFq12, power: usize) -> MyFq12 {
     let neg_one: BigUint = Fq::from(-1).into();
@@ -81,6 +84,79 @@ pub fn pow_native(a: MyFq12, exp: Vec<u64>) -> MyFq12 {
     res
 }
 
+// (a + b * y)^2 = (a^2 + ξ * b^2) + 2 * a * b * y, where Fq4 = Fq2[y] / (y^2 - ξ)
+fn fq4_square_native(a: Fq2, b: Fq2) -> (Fq2, Fq2) {
+    let mut b_sq = b.square();
+    Fq6Config::mul_fp2_by_nonresidue_in_place(&mut b_sq);
+    (a.square() + b_sq, (a * b).double())
+}
+
+/// Granger–Scott squaring in the cyclotomic subgroup, <https://eprint.iacr.org/2009/565.pdf>.
+pub fn cyclotomic_square_native(a: MyFq12) -> MyFq12 {
+    let fq2 = |i: usize| Fq2::new(a.coeffs[i], a.coeffs[i + 6]);
+    let (z0, z1, z2, z3, z4, z5) = (fq2(0), fq2(3), fq2(1), fq2(4), fq2(2), fq2(5));
+
+    let (t0, t1) = fq4_square_native(z0, z1);
+    let (t2, t3) = fq4_square_native(z2, z3);
+    let (t4, t5) = fq4_square_native(z4, z5);
+
+    // z0 = 3 * t0 - 2 * z0
+    let z0 = (t0 - z0).double() + t0;
+    // z1 = 3 * t1 + 2 * z1
+    let z1 = (t1 + z1).double() + t1;
+    // z2 = 3 * ξ * t5 + 2 * z2
+    let mut t5 = t5;
+    Fq6Config::mul_fp2_by_nonresidue_in_place(&mut t5);
+    let z2 = (t5 + z2).double() + t5;
+    // z3 = 3 * t4 - 2 * z3
+    let z3 = (t4 - z3).double() + t4;
+    // z4 = 3 * t2 - 2 * z4
+    let z4 = (t2 - z4).double() + t2;
+    // z5 = 3 * t3 + 2 * z5
+    let z5 = (t3 + z5).double() + t3;
+
+    let out = [z0, z2, z4, z1, z3, z5];
+    let out_coeffs = out
+        .iter()
+        .map(|x| x.c0)
+        .chain(out.iter().map(|x| x.c1))
+        .collect_vec();
+
+    MyFq12 {
+        coeffs: out_coeffs.try_into().unwrap(),
+    }
+}
+
+/// Computes `a^x` for `a` in the cyclotomic subgroup, where `x` is the BLS parameter.
+pub fn cyclotomic_exp_by_x_native(a: MyFq12) -> MyFq12 {
+    let mut res = a;
+    let mut is_started = false;
+    let naf = get_naf(vec![BLS_X]);
+
+    for &z in naf.iter().rev() {
+        if is_started {
+            res = cyclotomic_square_native(res);
+        }
+
+        if z != 0 {
+            assert!(z == 1 || z == -1);
+            if is_started {
+                res = res * a;
+            } else {
+                assert_eq!(z, 1);
+                is_started = true;
+            }
+        }
+    }
+
+    // the inverse of a cyclotomic element is its conjugate
+    if BLS_X_IS_NEGATIVE {
+        conjugate_fp12(res)
+    } else {
+        res
+    }
+}
+
 pub fn get_naf(mut exp: Vec<u64>) -> Vec<i8> {
     // https://en.wikipedia.org/wiki/Non-adjacent_form
     // NAF for exp:
@@ -129,6 +205,16 @@ pub fn get_naf(mut exp: Vec<u64>) -> Vec<i8> {
     naf
 }
 
+pub fn mod_inverse(a: &BigUint, modulus: &BigUint) -> BigUint {
+    let a = BigInt::from(a % modulus);
+    let modulus = BigInt::from(modulus.clone());
+    let egcd = a.extended_gcd(&modulus);
+    assert!(egcd.gcd.is_one());
+    ((egcd.x % &modulus + &modulus) % &modulus)
+        .to_biguint()
+        .unwrap()
+}
+
 pub fn conjugate_fp12(a: MyFq12) -> MyFq12 {
     let coeffs: Vec<Fq> = a
         .coeffs
@@ -147,3 +233,26 @@ pub fn conjugate_fp2(x: Fq2) -> Fq2 {
         c1: -x.c1,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fq12;
+    use ark_ff::{CyclotomicMultSubgroup, Field, UniformRand};
+
+    use super::{cyclotomic_exp_by_x_native, cyclotomic_square_native};
+    use crate::{native::final_exponentiation::ark_easy_part, utils::constants::BLS_X};
+
+    #[test]
+    fn test_cyclotomic_square_native() {
+        let rng = &mut rand::thread_rng();
+        let x = ark_easy_part(Fq12::rand(rng)).unwrap();
+        // x is negative
+        let x_exp = x.pow([BLS_X]).inverse().unwrap();
+
+        let squared: Fq12 = cyclotomic_square_native(x.into()).into();
+        assert_eq!(squared, x.cyclotomic_square());
+
+        let exp: Fq12 = cyclotomic_exp_by_x_native(x.into()).into();
+        assert_eq!(exp, x_exp);
+    }
+}
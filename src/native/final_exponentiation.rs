@@ -1,7 +1,11 @@
-use ark_bls12_381::Fq12;
-use ark_ff::{CyclotomicMultSubgroup, Field};
-
-use super::fin_exp_helpers::{conjugate_fp12, frobenius_map_native, pow_native};
+use ark_bls12_381::{Fq, Fq12, Fq6, Fr};
+use ark_ff::{CyclotomicMultSubgroup, Field, PrimeField};
+use num::BigUint;
+
+use super::fin_exp_helpers::{
+    conjugate_fp12, cyclotomic_exp_by_x_native, cyclotomic_square_native, frobenius_map_native,
+    mod_inverse,
+};
 use crate::utils::{constants::BLS_X, helpers::MyFq12};
 
 // out = in^{ (q^6 - 1)*(q^2 + 1) }
@@ -42,26 +46,21 @@ pub fn ark_easy_part(f: Fq12) -> Option<Fq12> {
 }
 
 pub fn hard_part_native(r: Fq12) -> Fq12 {
-    let mut y0 = r.square();
-    let mut y1: Fq12 = pow_native(r.into(), vec![BLS_X]).into();
-    y1 = *y1.conjugate_in_place();
+    let mut y0: Fq12 = cyclotomic_square_native(r.into()).into();
+    let mut y1: Fq12 = cyclotomic_exp_by_x_native(r.into()).into();
     let mut y2 = r;
     y2 = *y2.conjugate_in_place();
 
     y1 *= y2;
-    y2 = pow_native(y1.into(), vec![BLS_X]).into();
-    y2 = *y2.conjugate_in_place();
+    y2 = cyclotomic_exp_by_x_native(y1.into()).into();
     y1 = *y1.conjugate_in_place();
     y1 *= y2;
-    y2 = pow_native(y1.into(), vec![BLS_X]).into();
-    y2 = *y2.conjugate_in_place();
+    y2 = cyclotomic_exp_by_x_native(y1.into()).into();
     y1 = frobenius_map_native(y1.into(), 1).into();
     y1 *= y2;
     let r = r * y0;
-    y0 = pow_native(y1.into(), vec![BLS_X]).into();
-    y0 = *y0.conjugate_in_place();
-    y2 = pow_native(y0.into(), vec![BLS_X]).into();
-    y2 = *y2.conjugate_in_place();
+    y0 = cyclotomic_exp_by_x_native(y1.into()).into();
+    y2 = cyclotomic_exp_by_x_native(y0.into()).into();
     y0 = y1;
     y0 = frobenius_map_native(y0.into(), 2).into();
     y1 = *y1.conjugate_in_place();
@@ -77,14 +76,52 @@ pub fn final_exponentiation(a: MyFq12) -> Fq12 {
     hard_part_native(f0.into())
 }
 
+/// Computes the residue witness `c` and the scaling factor `s ∈ Fq6` such that
+/// `f * s = c^(p - x)`, following <https://eprint.iacr.org/2024/640.pdf>.
+/// They only exist when `f^((p^12 - 1) / r) = 1`, i.e. when `f` is the Miller
+/// loop output of a pairing product equal to one.
+pub fn residue_witness(f: Fq12) -> (Fq12, Fq6) {
+    let p: BigUint = Fq::MODULUS.into();
+    let r: BigUint = Fr::MODULUS.into();
+    let h = (p.pow(12) - 1u32) / r;
+    // h = 27 * m * k with m = (1 - x) / 3 and gcd(27 * m, k) = 1
+    let m = (BigUint::from(BLS_X) + 1u32) / 3u32;
+    let k = &h / (&m * 27u32);
+    // λ = p - x is a multiple of r, since p = x mod r
+    let lambda = &p + BLS_X;
+
+    // cancel the order m part of f
+    let e = &k * 27u32;
+    let t = &m - mod_inverse(&e, &m);
+    let s_m = f.pow(e.to_u64_digits()).pow(t.to_u64_digits());
+
+    // cancel the order 27 part of f
+    let e = &k * &m;
+    let order = BigUint::from(27u32);
+    let t = &order - mod_inverse(&e, &order);
+    let s_27 = f.pow(e.to_u64_digits()).pow(t.to_u64_digits());
+
+    // 27 * m divides p^6 - 1, so the scaling factor lies in Fq6
+    let s = s_m * s_27;
+    let c = (f * s).pow(mod_inverse(&lambda, &k).to_u64_digits());
+
+    (c, s.c0)
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_bls12_381::{Fq12, Fr};
-    use ark_ec::pairing::{MillerLoopOutput, Pairing};
+    use ark_bls12_381::{Fq12, Fr, G1Affine, G2Affine};
+    use ark_ec::{
+        pairing::{MillerLoopOutput, Pairing},
+        CurveGroup,
+    };
     use ark_ff::{Field, UniformRand};
 
-    use crate::native::final_exponentiation::{
-        ark_easy_part, final_exponentiation, hard_part_native,
+    use crate::{
+        native::final_exponentiation::{
+            ark_easy_part, final_exponentiation, hard_part_native, residue_witness,
+        },
+        utils::constants::BLS_X,
     };
 
     #[test]
@@ -170,4 +207,22 @@ mod tests {
         let expected_result = rand_x.pow(hard_part.to_u64_digits());
         assert_eq!(ark_hard_part, expected_result);
     }
+
+    #[test]
+    fn test_residue_witness() {
+        let rng = &mut rand::thread_rng();
+        let s = Fr::rand(rng);
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let sp = (p * s).into_affine();
+        let sq = (q * s).into_affine();
+        let f = ark_bls12_381::Bls12_381::multi_miller_loop([sp, -p], [q, sq]).0;
+
+        let (c, s) = residue_witness(f);
+        let s = Fq12::new(s, Default::default());
+        let mut c_p = c;
+        c_p.frobenius_map_in_place(1);
+
+        assert_eq!(f * s, c_p * c.pow([BLS_X]));
+    }
 }
@@ -1,7 +1,7 @@
 use ark_bls12_381::{Fq, Fq12, Fq2, G1Affine, G2Affine};
 use ark_ec::{short_weierstrass::SWCurveConfig, AffineRepr};
 use ark_ff::{vec::IntoIter, AdditiveGroup, BitIteratorBE, Field, UniformRand};
-use ark_std::{cfg_chunks_mut, One};
+use ark_std::One;
 
 use crate::utils::constants::{BLS_X, BLS_X_IS_NEGATIVE};
 
@@ -132,23 +132,18 @@ pub fn multi_miller_loop_native(
         })
         .collect::<Vec<_>>();
 
-    let mut f: Fq12 = cfg_chunks_mut!(pairs, 4)
-        .map(|pairs| {
-            let mut f = Fq12::ONE;
-            for i in BitIteratorBE::without_leading_zeros([BLS_X]).skip(1) {
-                f.square_in_place();
-                for (p, coeffs) in pairs.iter_mut() {
-                    ell(&mut f, coeffs.next().unwrap(), p.0);
-                }
-                if i {
-                    for (p, coeffs) in pairs.iter_mut() {
-                        ell(&mut f, coeffs.next().unwrap(), p.0);
-                    }
-                }
+    let mut f = Fq12::ONE;
+    for i in BitIteratorBE::without_leading_zeros([BLS_X]).skip(1) {
+        f.square_in_place();
+        for (p, coeffs) in pairs.iter_mut() {
+            ell(&mut f, coeffs.next().unwrap(), p.0);
+        }
+        if i {
+            for (p, coeffs) in pairs.iter_mut() {
+                ell(&mut f, coeffs.next().unwrap(), p.0);
             }
-            f
-        })
-        .product();
+        }
+    }
 
     if BLS_X_IS_NEGATIVE {
         f.conjugate_in_place();
@@ -1,4 +1,5 @@
 use ark_bls12_381::Fq12;
+use ark_std::One;
 
 use super::{
     final_exponentiation::final_exponentiation,
@@ -12,15 +13,38 @@ pub fn native_pairing(
     final_exponentiation(multi_miller_loop_native(a, b).into())
 }
 
+/// Returns whether `∏ e(Pᵢ, Qᵢ) = 1`, mirroring `pairing::pairing_check`.
+pub fn native_pairing_check(
+    pairs: impl IntoIterator<Item = (impl Into<G1Prepared>, impl Into<G2Prepared>)>,
+) -> bool {
+    let (a, b): (Vec<G1Prepared>, Vec<G2Prepared>) =
+        pairs.into_iter().map(|(p, q)| (p.into(), q.into())).unzip();
+    native_pairing(a, b).is_one()
+}
+
+/// Returns whether `∏ e(±Pᵢ, Qᵢ) = 1`, mirroring `pairing::signed_pairing_check`.
+pub fn native_signed_pairing_check(
+    pairs: impl IntoIterator<Item = (bool, impl Into<G1Prepared>, impl Into<G2Prepared>)>,
+) -> bool {
+    native_pairing_check(pairs.into_iter().map(|(negate, p, q)| {
+        let p = p.into();
+        let p = match negate {
+            true => G1Prepared(-p.0),
+            false => p,
+        };
+        (p, q.into())
+    }))
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_bls12_381::{G1Affine, G2Affine};
-    use ark_ec::pairing::Pairing;
+    use ark_bls12_381::{Fr, G1Affine, G2Affine};
+    use ark_ec::{pairing::Pairing, CurveGroup};
     use ark_ff::UniformRand;
 
     use crate::native::{
         miller_loop::{G1Prepared, G2Prepared},
-        pairing::native_pairing,
+        pairing::{native_pairing, native_pairing_check, native_signed_pairing_check},
     };
 
     #[test]
@@ -32,4 +56,27 @@ mod tests {
         let native_pairing = native_pairing([G1Prepared(p0)], [G2Prepared::from(q0)]);
         assert_eq!(ark_pairing, native_pairing);
     }
+
+    #[test]
+    fn test_native_pairing_check() {
+        let rng = &mut rand::thread_rng();
+        let s = Fr::rand(rng);
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let sp = (p * s).into_affine();
+        let sq = (q * s).into_affine();
+
+        assert!(native_pairing_check([
+            (G1Prepared(sp), q),
+            (G1Prepared(-p), sq)
+        ]));
+        assert!(!native_pairing_check([
+            (G1Prepared(sp), q),
+            (G1Prepared(p), sq)
+        ]));
+        assert!(native_signed_pairing_check([
+            (false, G1Prepared(sp), q),
+            (true, G1Prepared(p), sq)
+        ]));
+    }
 }
@@ -6,7 +6,7 @@ use plonky2::{
 use crate::{
     curves::{g1::G1PreparedTarget, g2::G2PreparedTarget},
     fields::fq12_target::Fq12Target,
-    final_exponentiation::final_exponentiation,
+    final_exponentiation::{assert_final_exponentiation_is_one, final_exponentiation},
     miller_loop::multi_miller_loop,
 };
 
@@ -19,10 +19,53 @@ pub fn pairing<F: RichField + Extendable<D>, const D: usize>(
     final_exponentiation::<F, D>(builder, f)
 }
 
+/// Asserts that `∏ e(Pᵢ, Qᵢ) = 1`. The final exponentiation is not computed, the
+/// Miller loop output is checked against a residue witness instead.
+pub fn pairing_check<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    pairs: impl IntoIterator<
+        Item = (
+            impl Into<G1PreparedTarget<F, D>>,
+            impl Into<G2PreparedTarget<F, D>>,
+        ),
+    >,
+) {
+    let (a, b): (Vec<G1PreparedTarget<F, D>>, Vec<G2PreparedTarget<F, D>>) =
+        pairs.into_iter().map(|(p, q)| (p.into(), q.into())).unzip();
+    let f = multi_miller_loop(builder, a, b);
+    assert_final_exponentiation_is_one(builder, &f);
+}
+
+/// Asserts that `∏ e(±Pᵢ, Qᵢ) = 1`, where `Pᵢ` is negated when its flag is set.
+/// `e(a, b) = e(c, d)` is then written as `[(false, a, b), (true, c, d)]`.
+pub fn signed_pairing_check<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    pairs: impl IntoIterator<
+        Item = (
+            bool,
+            impl Into<G1PreparedTarget<F, D>>,
+            impl Into<G2PreparedTarget<F, D>>,
+        ),
+    >,
+) {
+    let pairs = pairs
+        .into_iter()
+        .map(|(negate, p, q)| {
+            let p = p.into();
+            let p = match negate {
+                true => G1PreparedTarget(p.0.neg(builder)),
+                false => p,
+            };
+            (p, q.into())
+        })
+        .collect::<Vec<(G1PreparedTarget<F, D>, G2PreparedTarget<F, D>)>>();
+    pairing_check(builder, pairs)
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_bls12_381::{G1Affine, G2Affine};
-    use ark_ec::pairing::Pairing;
+    use ark_bls12_381::{Fr, G1Affine, G2Affine};
+    use ark_ec::{pairing::Pairing, CurveGroup};
     use ark_ff::UniformRand;
     use plonky2::{
         field::goldilocks_field::GoldilocksField,
@@ -33,7 +76,7 @@ mod tests {
         },
     };
 
-    use super::pairing;
+    use super::{pairing, pairing_check, signed_pairing_check};
     use crate::{
         curves::{
             g1::{G1AffineTarget, G1PreparedTarget},
@@ -69,4 +112,89 @@ mod tests {
         let data = builder.build::<C>();
         let _proof = data.prove(pw);
     }
+
+    #[test]
+    fn test_pairing_check() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let s = Fr::rand(rng);
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let sp = (p * s).into_affine();
+        let sq = (q * s).into_affine();
+
+        // e(sP, Q) * e(-P, sQ) = 1
+        let sp_t = G1PreparedTarget(G1AffineTarget::constant(&mut builder, sp));
+        let neg_p_t = G1PreparedTarget(G1AffineTarget::constant(&mut builder, -p));
+        let q_t = G2AffineTarget::constant(&mut builder, q);
+        let q_t = G2PreparedTarget::from(&mut builder, q_t);
+        let sq_t = G2AffineTarget::constant(&mut builder, sq);
+        let sq_t = G2PreparedTarget::from(&mut builder, sq_t);
+        pairing_check(&mut builder, [(sp_t, q_t), (neg_p_t, sq_t)]);
+
+        let pw = PartialWitness::<F>::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_pairing_check_line_witness() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let s = Fr::rand(rng);
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let sp = (p * s).into_affine();
+        let sq = (q * s).into_affine();
+
+        // e(sP, Q) * e(-P, sQ) = 1, with lines scaled by Fq2 factors that the residue
+        // witness has to absorb
+        let sp_t = G1PreparedTarget(G1AffineTarget::constant(&mut builder, sp));
+        let neg_p_t = G1PreparedTarget(G1AffineTarget::constant(&mut builder, -p));
+        let q_t = G2AffineTarget::add_virtual(&mut builder);
+        let q_prepared_t = G2PreparedTarget::from_line_witness(&mut builder, q_t.clone());
+        let sq_t = G2AffineTarget::add_virtual(&mut builder);
+        let sq_prepared_t = G2PreparedTarget::from_line_witness(&mut builder, sq_t.clone());
+        pairing_check(
+            &mut builder,
+            [(sp_t, q_prepared_t), (neg_p_t, sq_prepared_t)],
+        );
+
+        let mut pw = PartialWitness::<F>::new();
+        q_t.set_witness(&mut pw, &q);
+        sq_t.set_witness(&mut pw, &sq);
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
+
+    #[test]
+    fn test_signed_pairing_check() {
+        let config = CircuitConfig::wide_ecc_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let rng = &mut rand::thread_rng();
+        let s = Fr::rand(rng);
+        let p = G1Affine::rand(rng);
+        let q = G2Affine::rand(rng);
+        let sp = (p * s).into_affine();
+        let sq = (q * s).into_affine();
+        assert_eq!(
+            ark_bls12_381::Bls12_381::pairing(sp, q),
+            ark_bls12_381::Bls12_381::pairing(p, sq)
+        );
+
+        // e(sP, Q) = e(P, sQ)
+        let sp_t = G1PreparedTarget(G1AffineTarget::constant(&mut builder, sp));
+        let p_t = G1PreparedTarget(G1AffineTarget::constant(&mut builder, p));
+        let q_t = G2AffineTarget::constant(&mut builder, q);
+        let q_t = G2PreparedTarget::from(&mut builder, q_t);
+        let sq_t = G2AffineTarget::constant(&mut builder, sq);
+        let sq_t = G2PreparedTarget::from(&mut builder, sq_t);
+        signed_pairing_check(&mut builder, [(false, sp_t, q_t), (true, p_t, sq_t)]);
+
+        let pw = PartialWitness::<F>::new();
+        let data = builder.build::<C>();
+        let _proof = data.prove(pw);
+    }
 }
@@ -1,3 +1,4 @@
 pub mod constants;
 pub mod helpers;
 pub mod my_fq6;
+pub mod serialization;
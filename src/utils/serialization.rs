@@ -0,0 +1,24 @@
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+use plonky2_ecdsa::gadgets::biguint::BigUintTarget;
+use plonky2_u32::gadgets::arithmetic_u32::U32Target;
+
+/// Writes the limb targets of each value, for generators that witness field elements.
+pub(crate) fn write_biguint_targets(dst: &mut Vec<u8>, xs: &[BigUintTarget]) -> IoResult<()> {
+    dst.write_usize(xs.len())?;
+    for x in xs {
+        let limbs = x.limbs.iter().map(|l| l.0).collect::<Vec<_>>();
+        dst.write_target_vec(&limbs)?;
+    }
+    Ok(())
+}
+
+/// Reads values written by [`write_biguint_targets`].
+pub(crate) fn read_biguint_targets(src: &mut Buffer) -> IoResult<Vec<BigUintTarget>> {
+    let len = src.read_usize()?;
+    (0..len)
+        .map(|_| {
+            let limbs = src.read_target_vec()?.into_iter().map(U32Target).collect();
+            Ok(BigUintTarget { limbs })
+        })
+        .collect()
+}